@@ -281,11 +281,53 @@ pub fn get_role_owner(
     mut sess: Session<MinimalRuntime>,
     vault: &AccountId32,
 ) -> Result<(AccountId32, Session<MinimalRuntime>), Box<dyn Error>> {
-    sess.call_with_address(vault.clone(), "get_role_owner", NO_ARGS, None)?;
+    sess.call_with_address(vault.clone(), "get_role", &["Owner"], None)?;
 
     let owner: Result<AccountId32, drink::errors::LangError> = sess.last_call_return().unwrap();
     Ok((owner.unwrap(), sess))
 }
+pub fn get_vault_role(
+    mut sess: Session<MinimalRuntime>,
+    vault: &AccountId32,
+    role_type: &str,
+) -> Result<(AccountId32, Session<MinimalRuntime>), Box<dyn Error>> {
+    sess.call_with_address(vault.clone(), "get_role", &[role_type], None)?;
+
+    let role: Result<AccountId32, drink::errors::LangError> = sess.last_call_return().unwrap();
+    Ok((role.unwrap(), sess))
+}
+pub fn get_vault_role_admin(
+    mut sess: Session<MinimalRuntime>,
+    vault: &AccountId32,
+    role_type: &str,
+) -> Result<(AccountId32, Session<MinimalRuntime>), Box<dyn Error>> {
+    sess.call_with_address(vault.clone(), "get_role_admin", &[role_type], None)?;
+
+    let admin: Result<AccountId32, drink::errors::LangError> = sess.last_call_return().unwrap();
+    Ok((admin.unwrap(), sess))
+}
+#[derive(Debug, PartialEq, scale::Decode)]
+pub enum VaultRoleType {
+    Owner,
+    AdjustFee,
+    FeeSplit,
+    Allowlist,
+    Pauser,
+}
+#[derive(Debug, scale::Decode)]
+pub struct PendingRole {
+    pub account: Option<AccountId32>,
+    pub admin: Option<AccountId32>,
+}
+pub fn get_pending_role_transfers(
+    mut sess: Session<MinimalRuntime>,
+    vault: &AccountId32,
+) -> Result<(Vec<(VaultRoleType, PendingRole)>, Session<MinimalRuntime>), Box<dyn Error>> {
+    sess.call_with_address(vault.clone(), "get_pending_role_transfers", NO_ARGS, None)?;
+
+    let pending: Result<Vec<(VaultRoleType, PendingRole)>, drink::errors::LangError> = sess.last_call_return().unwrap();
+    Ok((pending.unwrap(), sess))
+}
 
 #[derive(Debug, scale::Decode)]
 pub struct Agent {
@@ -423,6 +465,39 @@ pub fn get_unlock_request_count(
     let count: Result<u128, drink::errors::LangError> = sess.last_call_return().unwrap();
     Ok((count.unwrap(), sess))
 }
+#[derive(Debug, scale::Decode)]
+pub struct UserUnlockSummary {
+    pub pending_shares: u128,
+    pub cooldown_azero: u128,
+    pub claimable_azero: u128,
+}
+pub fn get_user_summary(
+    mut sess: Session<MinimalRuntime>,
+    vault: &AccountId32,
+    user: &AccountId32,
+) -> Result<(UserUnlockSummary, Session<MinimalRuntime>), Box<dyn Error>> {
+    sess.set_transcoder(vault.clone(), &transcoder_vault().unwrap());
+    sess.call_with_address(
+        vault.clone(),
+        "get_user_summary",
+        &[user.to_string()],
+        None,
+    )?;
+
+    let summary: Result<UserUnlockSummary, drink::errors::LangError> = sess.last_call_return().unwrap();
+    Ok((summary.unwrap(), sess))
+}
+#[derive(Debug, scale::Decode)]
+pub struct AgentApy {
+    pub agent: AccountId32,
+    pub apy: u32,
+}
+#[derive(Debug, scale::Decode)]
+pub struct ApyEstimate {
+    pub gross_apy: u32,
+    pub net_apy: u32,
+    pub agents: Vec<AgentApy>,
+}
 pub fn get_batch_unlock_requests(
     mut sess: Session<MinimalRuntime>,
     vault: &AccountId32,
@@ -465,7 +540,7 @@ pub fn call_redeem(
     mut sess: Session<MinimalRuntime>,
     vault: &AccountId32,
     sender: &AccountId32,
-    index: u64,
+    unlock_id: u128,
 ) -> Result<(u128, Session<MinimalRuntime>), Box<dyn Error>> {
     let prev_balance = sess.chain_api().balance(&sender);
 
@@ -474,7 +549,7 @@ pub fn call_redeem(
         &vault,
         &sender,
         String::from("redeem"),
-        Some([sender.clone().to_string(), index.to_string()].to_vec()),
+        Some([sender.clone().to_string(), unlock_id.to_string()].to_vec()),
         None,
         transcoder_vault(),
     )?;
@@ -489,7 +564,7 @@ pub fn call_redeem_with_withdraw(
     mut sess: Session<MinimalRuntime>,
     vault: &AccountId32,
     sender: &AccountId32,
-    index: u64,
+    unlock_id: u128,
 ) -> Result<(u128, Session<MinimalRuntime>), Box<dyn Error>> {
     let prev_balance = sess.chain_api().balance(&sender);
 
@@ -498,7 +573,7 @@ pub fn call_redeem_with_withdraw(
         &vault,
         &sender,
         String::from("redeem_with_withdraw"),
-        Some([sender.clone().to_string(), index.to_string()].to_vec()),
+        Some([sender.clone().to_string(), unlock_id.to_string()].to_vec()),
         None,
         transcoder_vault(),
     )?;
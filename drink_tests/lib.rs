@@ -335,13 +335,13 @@ mod tests {
         // Redeem AZERO minus fees
         let (redeemed, sess) = helpers::call_redeem_with_withdraw(sess, &ctx.vault, &ctx.alice, 0).unwrap();
         assert_eq!(redeemed, 10_000e10 as u128 + 32 - 2 - fees_50000_staked_2_days_azero / 5);
-        let (redeemed, sess) = helpers::call_redeem(sess, &ctx.vault, &ctx.bob, 0).unwrap();
+        let (redeemed, sess) = helpers::call_redeem(sess, &ctx.vault, &ctx.bob, 1).unwrap();
         assert_eq!(redeemed, 10_000e10 as u128 + 32 - 1 - fees_50000_staked_2_days_azero / 5);
-        let (redeemed, sess) = helpers::call_redeem(sess, &ctx.vault, &ctx.charlie, 0).unwrap();
+        let (redeemed, sess) = helpers::call_redeem(sess, &ctx.vault, &ctx.charlie, 2).unwrap();
         assert_eq!(redeemed, 10_000e10 as u128 + 32 - 1 - fees_50000_staked_2_days_azero / 5);
-        let (redeemed, sess) = helpers::call_redeem(sess, &ctx.vault, &ctx.dave, 0).unwrap();
+        let (redeemed, sess) = helpers::call_redeem(sess, &ctx.vault, &ctx.dave, 3).unwrap();
         assert_eq!(redeemed, 10_000e10 as u128 + 32 - 1 - fees_50000_staked_2_days_azero / 5);
-        let (redeemed, sess) = helpers::call_redeem(sess, &ctx.vault, &ctx.ed, 0).unwrap();
+        let (redeemed, sess) = helpers::call_redeem(sess, &ctx.vault, &ctx.ed, 4).unwrap();
         assert_eq!(redeemed, 10_000e10 as u128 + 32 - 1 - fees_50000_staked_2_days_azero / 5);
 
         let (claimable_fees, sess) = helpers::get_current_virtual_shares(sess, &ctx.vault).unwrap();
@@ -561,13 +561,13 @@ mod tests {
         let fees = 32843419000;
         let (redeemed, sess) = helpers::call_redeem_with_withdraw(sess, &ctx.vault, &ctx.alice, 0).unwrap();
         assert_eq!(redeemed, 10_000e10 as u128 + 31 - fees);
-        let (redeemed, sess) = helpers::call_redeem(sess, &ctx.vault, &ctx.bob, 0).unwrap();
+        let (redeemed, sess) = helpers::call_redeem(sess, &ctx.vault, &ctx.bob, 1).unwrap();
         assert_eq!(redeemed, 10_000e10 as u128 + 32 - fees);
-        let (redeemed, sess) = helpers::call_redeem(sess, &ctx.vault, &ctx.charlie, 0).unwrap();
+        let (redeemed, sess) = helpers::call_redeem(sess, &ctx.vault, &ctx.charlie, 2).unwrap();
         assert_eq!(redeemed, 10_000e10 as u128 + 32 - fees);
-        let (redeemed, sess) = helpers::call_redeem(sess, &ctx.vault, &ctx.dave, 0).unwrap();
+        let (redeemed, sess) = helpers::call_redeem(sess, &ctx.vault, &ctx.dave, 3).unwrap();
         assert_eq!(redeemed, 10_000e10 as u128 + 32 - fees);
-        let (redeemed, _sess) = helpers::call_redeem(sess, &ctx.vault, &ctx.ed, 0).unwrap();
+        let (redeemed, _sess) = helpers::call_redeem(sess, &ctx.vault, &ctx.ed, 4).unwrap();
         assert_eq!(redeemed, 10_000e10 as u128 + 32 - fees);
 
         Ok(())
@@ -721,7 +721,7 @@ mod tests {
             &ctx.vault,
             &ctx.bob,
             String::from("cancel_unlock_request"),
-            Some(vec![String::from("0")]),
+            Some(vec![String::from("1")]),
             None,
             helpers::transcoder_vault(),
         )
@@ -731,7 +731,7 @@ mod tests {
             &ctx.vault,
             &ctx.bob,
             String::from("cancel_unlock_request"),
-            Some(vec![String::from("0")]),
+            Some(vec![String::from("5")]),
             None,
             helpers::transcoder_vault(),
         )
@@ -741,7 +741,7 @@ mod tests {
             &ctx.vault,
             &ctx.bob,
             String::from("cancel_unlock_request"),
-            Some(vec![String::from("0")]),
+            Some(vec![String::from("9")]),
             None,
             helpers::transcoder_vault(),
         )
@@ -753,7 +753,7 @@ mod tests {
             &ctx.vault,
             &ctx.charlie,
             String::from("cancel_unlock_request"),
-            Some(vec![String::from("2")]),
+            Some(vec![String::from("10")]),
             None,
             helpers::transcoder_vault(),
         )
@@ -763,7 +763,7 @@ mod tests {
             &ctx.vault,
             &ctx.charlie,
             String::from("cancel_unlock_request"),
-            Some(vec![String::from("1")]),
+            Some(vec![String::from("6")]),
             None,
             helpers::transcoder_vault(),
         )
@@ -773,7 +773,7 @@ mod tests {
             &ctx.vault,
             &ctx.charlie,
             String::from("cancel_unlock_request"),
-            Some(vec![String::from("0")]),
+            Some(vec![String::from("2")]),
             None,
             helpers::transcoder_vault(),
         )
@@ -1094,8 +1094,8 @@ mod tests {
             ctx.sess,
             &ctx.vault,
             &ctx.alice,
-            String::from("transfer_role_owner"),
-            None,
+            String::from("propose_role_transfer"),
+            Some([String::from("Owner"), ctx.alice.to_string()].to_vec()),
             None,
             helpers::transcoder_vault(),
         ) {
@@ -1110,13 +1110,29 @@ mod tests {
         let (owner, sess) = helpers::get_role_owner(ctx.sess, &ctx.vault).unwrap();
         assert_eq!(owner, ctx.bob);
 
-        // Transfer owner role to Charlie
+        // Propose owner role to Charlie
         let sess = helpers::call_function(
             sess,
             &ctx.vault,
             &owner,
-            String::from("transfer_role_owner"),
-            Some([ctx.charlie.to_string()].to_vec()),
+            String::from("propose_role_transfer"),
+            Some([String::from("Owner"), ctx.charlie.to_string()].to_vec()),
+            None,
+            helpers::transcoder_vault(),
+        )
+            .unwrap();
+
+        // Role is not transferred until accepted
+        let (owner, sess) = helpers::get_role_owner(sess, &ctx.vault).unwrap();
+        assert_eq!(owner, ctx.bob);
+
+        // Charlie accepts the owner role
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.charlie,
+            String::from("accept_role"),
+            Some([String::from("Owner")].to_vec()),
             None,
             helpers::transcoder_vault(),
         )
@@ -1126,6 +1142,34 @@ mod tests {
         assert_eq!(owner, ctx.charlie);
     }
     #[test]
+    fn test_vault_accept_role_panic_because_not_proposed() {
+        let ctx = setup().unwrap();
+
+        let sess = helpers::call_function(
+            ctx.sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("propose_role_transfer"),
+            Some([String::from("Pauser"), ctx.charlie.to_string()].to_vec()),
+            None,
+            helpers::transcoder_vault(),
+        )
+            .unwrap();
+
+        match helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.alice,
+            String::from("accept_role"),
+            Some([String::from("Pauser")].to_vec()),
+            None,
+            helpers::transcoder_vault(),
+        ) {
+            Ok(_) => panic!("Should panic because Alice was not proposed for the role"),
+            Err(_) => (),
+        };
+    }
+    #[test]
     fn test_nominator_add_agent_role_flow() {
         let ctx = setup().unwrap();
 
@@ -1679,13 +1723,13 @@ mod tests {
         let fee_split = 1 + expected_fees / 5;
         let (redeemed, sess) = helpers::call_redeem_with_withdraw(sess, &ctx.vault, &ctx.alice, 0).unwrap();
         assert_eq!(redeemed, 1000000 + 32 - fee_split - 1);
-        let (redeemed, sess) = helpers::call_redeem(sess, &ctx.vault, &ctx.bob, 0).unwrap();
+        let (redeemed, sess) = helpers::call_redeem(sess, &ctx.vault, &ctx.bob, 1).unwrap();
         assert_eq!(redeemed, 1000000 + 32 - fee_split);
-        let (redeemed, sess) = helpers::call_redeem(sess, &ctx.vault, &ctx.charlie, 0).unwrap();
+        let (redeemed, sess) = helpers::call_redeem(sess, &ctx.vault, &ctx.charlie, 2).unwrap();
         assert_eq!(redeemed, 1000000 + 32 - fee_split);
-        let (redeemed, sess) = helpers::call_redeem(sess, &ctx.vault, &ctx.dave, 0).unwrap();
+        let (redeemed, sess) = helpers::call_redeem(sess, &ctx.vault, &ctx.dave, 3).unwrap();
         assert_eq!(redeemed, 1000000 + 32 - fee_split);
-        let (redeemed, mut sess) = helpers::call_redeem(sess, &ctx.vault, &ctx.ed, 0).unwrap();
+        let (redeemed, mut sess) = helpers::call_redeem(sess, &ctx.vault, &ctx.ed, 4).unwrap();
         assert_eq!(redeemed, 1000000 + 32 - fee_split);
 
         let vault_balance = sess.chain_api().balance(&ctx.vault);
@@ -1765,19 +1809,19 @@ mod tests {
 
         let fee_split = 1 + expected_fees / 5;
         let (redeemed0, sess) = helpers::call_redeem_with_withdraw(sess, &ctx.vault, &ctx.alice, 0).unwrap();
-        let (redeemed1, sess) = helpers::call_redeem(sess, &ctx.vault, &ctx.alice, 0).unwrap();
+        let (redeemed1, sess) = helpers::call_redeem(sess, &ctx.vault, &ctx.alice, 5).unwrap();
         assert_eq!(redeemed0 + redeemed1, 1000000 + 64 - fee_split - 1);
-        let (redeemed0, sess) = helpers::call_redeem(sess, &ctx.vault, &ctx.bob, 0).unwrap();
-        let (redeemed1, sess) = helpers::call_redeem(sess, &ctx.vault, &ctx.bob, 0).unwrap();
+        let (redeemed0, sess) = helpers::call_redeem(sess, &ctx.vault, &ctx.bob, 1).unwrap();
+        let (redeemed1, sess) = helpers::call_redeem(sess, &ctx.vault, &ctx.bob, 6).unwrap();
         assert_eq!(redeemed0 + redeemed1, 1000000 + 64 - fee_split);
-        let (redeemed0, sess) = helpers::call_redeem(sess, &ctx.vault, &ctx.charlie, 0).unwrap();
-        let (redeemed1, sess) = helpers::call_redeem(sess, &ctx.vault, &ctx.charlie, 0).unwrap();
+        let (redeemed0, sess) = helpers::call_redeem(sess, &ctx.vault, &ctx.charlie, 2).unwrap();
+        let (redeemed1, sess) = helpers::call_redeem(sess, &ctx.vault, &ctx.charlie, 7).unwrap();
         assert_eq!(redeemed0 + redeemed1, 1000000 + 64 - fee_split);
-        let (redeemed0, sess) = helpers::call_redeem(sess, &ctx.vault, &ctx.dave, 0).unwrap();
-        let (redeemed1, sess) = helpers::call_redeem(sess, &ctx.vault, &ctx.dave, 0).unwrap();
+        let (redeemed0, sess) = helpers::call_redeem(sess, &ctx.vault, &ctx.dave, 3).unwrap();
+        let (redeemed1, sess) = helpers::call_redeem(sess, &ctx.vault, &ctx.dave, 8).unwrap();
         assert_eq!(redeemed0 + redeemed1, 1000000 + 64 - fee_split);
-        let (redeemed0, sess) = helpers::call_redeem(sess, &ctx.vault, &ctx.ed, 0).unwrap();
-        let (redeemed1, mut sess) = helpers::call_redeem(sess, &ctx.vault, &ctx.ed, 0).unwrap();
+        let (redeemed0, sess) = helpers::call_redeem(sess, &ctx.vault, &ctx.ed, 4).unwrap();
+        let (redeemed1, mut sess) = helpers::call_redeem(sess, &ctx.vault, &ctx.ed, 9).unwrap();
         assert_eq!(redeemed0 + redeemed1, 1000000 + 64 - fee_split);
 
         let vault_balance = sess.chain_api().balance(&ctx.vault);
@@ -1913,4 +1957,2242 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_instant_unlock_flow() -> Result<(), Box<dyn Error>> {
+        let ctx = setup().unwrap();
+
+        // Retain 10% of deposits in the liquidity buffer
+        let sess = helpers::call_function(
+            ctx.sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("adjust_buffer_target"),
+            Some(vec![String::from("1000")]), // 10%
+            None,
+            helpers::transcoder_vault(),
+        )
+            .unwrap();
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("adjust_max_instant_unlock"),
+            Some(vec![String::from("1000000")]),
+            None,
+            helpers::transcoder_vault(),
+        )
+            .unwrap();
+
+        // Stake 1m AZERO
+        let (_, sess) = helpers::call_stake(sess, &ctx.vault, &ctx.share_token, &ctx.alice, 1_000_000).unwrap();
+
+        let (total_pooled, mut sess) = helpers::get_total_pooled(sess, &ctx.vault).unwrap();
+        assert_eq!(total_pooled, 900_000, "Should bond 90% of the stake");
+
+        // Instantly unlock 50k sAZERO
+        let balance_before = sess.chain_api().balance(&ctx.alice);
+        let mut sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.alice,
+            String::from("instant_unlock"),
+            Some(vec![String::from("50000"), String::from("49850")]),
+            None,
+            helpers::transcoder_vault(),
+        )
+            .unwrap();
+        let balance_after = sess.chain_api().balance(&ctx.alice);
+
+        // 50,000 * (100% - 0.30%) = 49,850
+        assert_eq!(balance_after - balance_before, 49_850);
+
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.alice,
+            String::from("get_liquidity_buffer"),
+            None,
+            None,
+            helpers::transcoder_vault(),
+        )
+            .unwrap();
+        let buffer: Result<u128, drink::errors::LangError> = sess.last_call_return().unwrap();
+        assert_eq!(buffer.unwrap(), 100_000 - 49_850);
+
+        // Instant unlock fee accrues to remaining stakers
+        let (azero, _sess) = helpers::get_azero_from_shares(sess, &ctx.vault, 950_000).unwrap();
+        assert_eq!(azero, 950_150);
+
+        Ok(())
+    }
+    #[test]
+    fn test_instant_unlock_panic_because_insufficient_liquidity() {
+        let ctx = setup().unwrap();
+
+        let sess = helpers::call_function(
+            ctx.sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("adjust_max_instant_unlock"),
+            Some(vec![String::from("1000000")]),
+            None,
+            helpers::transcoder_vault(),
+        )
+            .unwrap();
+
+        // Stake 1m AZERO without a liquidity buffer
+        let (_, sess) = helpers::call_stake(sess, &ctx.vault, &ctx.share_token, &ctx.alice, 1_000_000).unwrap();
+
+        match helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.alice,
+            String::from("instant_unlock"),
+            Some(vec![String::from("1000"), String::from("0")]),
+            None,
+            helpers::transcoder_vault(),
+        ) {
+            Ok(_) => panic!("Should panic because the liquidity buffer is empty"),
+            Err(_) => (),
+        }
+    }
+    #[test]
+    fn test_stake_with_min_shares_panic_because_slippage() {
+        let ctx = setup().unwrap();
+
+        // Expecting more shares than AZERO staked at a 1:1 redemption ratio
+        match helpers::call_function(
+            ctx.sess,
+            &ctx.vault,
+            &ctx.alice,
+            String::from("stake_with_min_shares"),
+            Some(vec![String::from("1000001"), u64::MAX.to_string()]),
+            Some(1_000_000),
+            helpers::transcoder_vault(),
+        ) {
+            Ok(_) => panic!("Should panic because fewer shares would be minted than expected"),
+            Err(_) => (),
+        }
+    }
+    #[test]
+    fn test_stake_with_min_shares_panic_because_deadline() {
+        let mut ctx = setup().unwrap();
+        let now = ctx.sess.chain_api().get_timestamp();
+        let sess = helpers::update_in_milliseconds(ctx.sess, helpers::SECOND);
+
+        match helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.alice,
+            String::from("stake_with_min_shares"),
+            Some(vec![String::from("0"), now.to_string()]),
+            Some(1_000_000),
+            helpers::transcoder_vault(),
+        ) {
+            Ok(_) => panic!("Should panic because the deadline has passed"),
+            Err(_) => (),
+        }
+    }
+    #[test]
+    fn test_request_unlock_with_min_azero_flow() -> Result<(), Box<dyn Error>> {
+        let ctx = setup().unwrap();
+
+        let (_, sess) = helpers::call_stake(ctx.sess, &ctx.vault, &ctx.share_token, &ctx.alice, 1_000_000).unwrap();
+
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.alice,
+            String::from("request_unlock_with_min_azero"),
+            Some(vec![String::from("1000"), String::from("1000"), u64::MAX.to_string()]),
+            None,
+            helpers::transcoder_vault(),
+        )
+            .unwrap();
+
+        let (result, _sess) = helpers::get_unlock_request_count(sess, &ctx.vault, &ctx.alice).unwrap();
+        assert_eq!(result, 1);
+
+        Ok(())
+    }
+    #[test]
+    fn test_request_unlock_with_min_azero_panic_because_slippage() {
+        let ctx = setup().unwrap();
+
+        let (_, sess) = helpers::call_stake(ctx.sess, &ctx.vault, &ctx.share_token, &ctx.alice, 1_000_000).unwrap();
+
+        // Expecting more AZERO than the spot value of the shares
+        match helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.alice,
+            String::from("request_unlock_with_min_azero"),
+            Some(vec![String::from("1000"), String::from("1001"), u64::MAX.to_string()]),
+            None,
+            helpers::transcoder_vault(),
+        ) {
+            Ok(_) => panic!("Should panic because the shares are worth less than expected"),
+            Err(_) => (),
+        }
+    }
+    #[test]
+    fn test_stake_for_beneficiary() -> Result<(), Box<dyn Error>> {
+        let ctx = setup().unwrap();
+
+        // Bob stakes 1m AZERO on behalf of Alice
+        let sess = helpers::call_function(
+            ctx.sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("stake_for"),
+            Some(vec![ctx.alice.to_string()]),
+            Some(1_000_000),
+            helpers::transcoder_vault(),
+        )
+            .unwrap();
+
+        let (shares_alice, sess) = helpers::query_token_balance(sess, &ctx.share_token, &ctx.alice).unwrap();
+        let (shares_bob, _sess) = helpers::query_token_balance(sess, &ctx.share_token, &ctx.bob).unwrap();
+        assert_eq!(shares_alice, 1_000_000);
+        assert_eq!(shares_bob, 0);
+
+        Ok(())
+    }
+    #[test]
+    fn test_request_unlock_for_owner_with_allowance() -> Result<(), Box<dyn Error>> {
+        let ctx = setup().unwrap();
+
+        let (_, sess) = helpers::call_stake(ctx.sess, &ctx.vault, &ctx.share_token, &ctx.alice, 1_000_000).unwrap();
+
+        // Alice approves Ed to unlock 1k sAZERO
+        let sess = helpers::call_function(
+            sess,
+            &ctx.share_token,
+            &ctx.alice,
+            String::from("PSP22::approve"),
+            Some(vec![ctx.ed.to_string(), 1000.to_string()]),
+            None,
+            helpers::transcoder_share_token(),
+        )
+            .unwrap();
+
+        // Ed requests an unlock on behalf of Alice
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.ed,
+            String::from("request_unlock_for"),
+            Some(vec![ctx.alice.to_string(), 1000.to_string()]),
+            None,
+            helpers::transcoder_vault(),
+        )
+            .unwrap();
+
+        let (result, sess) = helpers::get_unlock_request_count(sess, &ctx.vault, &ctx.alice).unwrap();
+        assert_eq!(result, 1);
+
+        // Allowance has been consumed
+        match helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.ed,
+            String::from("request_unlock_for"),
+            Some(vec![ctx.alice.to_string(), 1000.to_string()]),
+            None,
+            helpers::transcoder_vault(),
+        ) {
+            Ok(_) => panic!("Should panic because Ed has no remaining allowance"),
+            Err(_) => (),
+        }
+
+        Ok(())
+    }
+    #[test]
+    fn test_unlock_request_transfer_flow() -> Result<(), Box<dyn Error>> {
+        let ctx = setup().unwrap();
+
+        let (_, sess) = helpers::call_stake(ctx.sess, &ctx.vault, &ctx.share_token, &ctx.alice, 1_000_000).unwrap();
+        let (_, sess) = helpers::call_request_unlock(sess, &ctx.vault, &ctx.share_token, &ctx.alice, 1000).unwrap();
+        let (_, sess) = helpers::call_request_unlock(sess, &ctx.vault, &ctx.share_token, &ctx.alice, 2000).unwrap();
+
+        // Alice transfers her first unlock request to Bob
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.alice,
+            String::from("transfer_unlock_request"),
+            Some(vec![ctx.bob.to_string(), String::from("0")]),
+            None,
+            helpers::transcoder_vault(),
+        )
+            .unwrap();
+
+        let (result, sess) = helpers::get_unlock_request_count(sess, &ctx.vault, &ctx.alice).unwrap();
+        assert_eq!(result, 1);
+        let (result, sess) = helpers::get_unlock_request_count(sess, &ctx.vault, &ctx.bob).unwrap();
+        assert_eq!(result, 1);
+
+        // Alice approves Charlie to transfer her remaining unlock request
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.alice,
+            String::from("approve_unlock_request"),
+            Some(vec![String::from("1"), format!("Some({})", ctx.charlie.to_string())]),
+            None,
+            helpers::transcoder_vault(),
+        )
+            .unwrap();
+
+        // Charlie transfers the unlock request to Dave
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.charlie,
+            String::from("transfer_unlock_request_from"),
+            Some(vec![ctx.alice.to_string(), ctx.dave.to_string(), String::from("1")]),
+            None,
+            helpers::transcoder_vault(),
+        )
+            .unwrap();
+
+        let (result, sess) = helpers::get_unlock_request_count(sess, &ctx.vault, &ctx.alice).unwrap();
+        assert_eq!(result, 0);
+        let (result, sess) = helpers::get_unlock_request_count(sess, &ctx.vault, &ctx.dave).unwrap();
+        assert_eq!(result, 1);
+
+        // Dave cancels the transferred unlock request and receives the shares
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.dave,
+            String::from("cancel_unlock_request"),
+            Some(vec![String::from("1")]),
+            None,
+            helpers::transcoder_vault(),
+        )
+            .unwrap();
+
+        let (shares_dave, _sess) = helpers::query_token_balance(sess, &ctx.share_token, &ctx.dave).unwrap();
+        assert_eq!(shares_dave, 2000);
+
+        Ok(())
+    }
+    #[test]
+    fn test_unlock_request_transfer_panic_because_not_approved() {
+        let ctx = setup().unwrap();
+
+        let (_, sess) = helpers::call_stake(ctx.sess, &ctx.vault, &ctx.share_token, &ctx.alice, 1_000_000).unwrap();
+        let (_, sess) = helpers::call_request_unlock(sess, &ctx.vault, &ctx.share_token, &ctx.alice, 1000).unwrap();
+
+        match helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.ed,
+            String::from("transfer_unlock_request_from"),
+            Some(vec![ctx.alice.to_string(), ctx.ed.to_string(), String::from("0")]),
+            None,
+            helpers::transcoder_vault(),
+        ) {
+            Ok(_) => panic!("Should panic because Ed is not approved by Alice"),
+            Err(_) => (),
+        }
+    }
+    #[test]
+    fn test_redeem_all_skips_pending_unlocks() -> Result<(), Box<dyn Error>> {
+        let ctx = setup().unwrap();
+
+        let (_, sess) = helpers::call_stake(ctx.sess, &ctx.vault, &ctx.share_token, &ctx.alice, 1_000_000).unwrap();
+
+        let (first_batch, sess) = helpers::query_batch_id(sess, &ctx.vault).unwrap();
+
+        // Two unlock requests in the first batch
+        let (_, sess) = helpers::call_request_unlock(sess, &ctx.vault, &ctx.share_token, &ctx.alice, 1000).unwrap();
+        let (_, sess) = helpers::call_request_unlock(sess, &ctx.vault, &ctx.share_token, &ctx.alice, 2000).unwrap();
+
+        // Wait for batch interval to pass
+        let sess = helpers::update_days(sess, 2);
+
+        // One unlock request in the second batch
+        let (_, sess) = helpers::call_request_unlock(sess, &ctx.vault, &ctx.share_token, &ctx.alice, 4000).unwrap();
+
+        let sess = helpers::call_send_batch_unlock_requests(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            vec![first_batch],
+        )
+            .unwrap();
+
+        // Wait for cooldown period to complete
+        let mut sess = helpers::update_days(sess, 14);
+
+        let balance_before = sess.chain_api().balance(&ctx.alice);
+        let mut sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.alice,
+            String::from("redeem_all_with_withdraw"),
+            Some(vec![ctx.alice.to_string()]),
+            None,
+            helpers::transcoder_vault(),
+        )
+            .unwrap();
+        let balance_after = sess.chain_api().balance(&ctx.alice);
+
+        // Only the first batch is redeemed (AZERO per share falls slightly due to 2 days of fees)
+        let redeemed = balance_after - balance_before;
+        assert!(redeemed > 2990 && redeemed <= 3000);
+
+        let (result, _sess) = helpers::get_unlock_request_count(sess, &ctx.vault, &ctx.alice).unwrap();
+        assert_eq!(result, 1, "Unlock request in the unsent batch should remain");
+
+        Ok(())
+    }
+    #[test]
+    fn test_user_unlock_summary() -> Result<(), Box<dyn Error>> {
+        let ctx = setup().unwrap();
+
+        let (_, sess) = helpers::call_stake(ctx.sess, &ctx.vault, &ctx.share_token, &ctx.alice, 1_000_000).unwrap();
+
+        let (first_batch, sess) = helpers::query_batch_id(sess, &ctx.vault).unwrap();
+        let (_, sess) = helpers::call_request_unlock(sess, &ctx.vault, &ctx.share_token, &ctx.alice, 1000).unwrap();
+
+        // Wait for batch interval to pass
+        let sess = helpers::update_days(sess, 2);
+
+        let (_, sess) = helpers::call_request_unlock(sess, &ctx.vault, &ctx.share_token, &ctx.alice, 2000).unwrap();
+
+        let (summary, sess) = helpers::get_user_summary(sess, &ctx.vault, &ctx.alice).unwrap();
+        assert_eq!(summary.pending_shares, 3000);
+        assert_eq!(summary.cooldown_azero, 0);
+        assert_eq!(summary.claimable_azero, 0);
+
+        let sess = helpers::call_send_batch_unlock_requests(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            vec![first_batch],
+        )
+            .unwrap();
+
+        let (summary, sess) = helpers::get_user_summary(sess, &ctx.vault, &ctx.alice).unwrap();
+        assert_eq!(summary.pending_shares, 2000);
+        assert!(summary.cooldown_azero > 0);
+        assert_eq!(summary.claimable_azero, 0);
+
+        // Wait for cooldown period to complete
+        let sess = helpers::update_days(sess, 14);
+
+        let (summary_after, _sess) = helpers::get_user_summary(sess, &ctx.vault, &ctx.alice).unwrap();
+        assert_eq!(summary_after.pending_shares, 2000);
+        assert_eq!(summary_after.cooldown_azero, 0);
+        assert_eq!(summary_after.claimable_azero, summary.cooldown_azero);
+
+        Ok(())
+    }
+    #[test]
+    fn test_unlock_partial_cancellation_from_previous_batch() -> Result<(), Box<dyn Error>> {
+        let ctx = setup().unwrap();
+
+        let (_, sess) = helpers::call_stake(ctx.sess, &ctx.vault, &ctx.share_token, &ctx.alice, 1_000_000).unwrap();
+
+        let (batch, sess) = helpers::query_batch_id(sess, &ctx.vault).unwrap();
+        let (_, sess) = helpers::call_request_unlock(sess, &ctx.vault, &ctx.share_token, &ctx.alice, 10_000).unwrap();
+
+        // Wait for batch interval to pass without the batch being sent
+        let sess = helpers::update_days(sess, 2);
+
+        // Alice cancels part of her unlock request
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.alice,
+            String::from("cancel_unlock_request_partial"),
+            Some(vec![String::from("0"), String::from("4000")]),
+            None,
+            helpers::transcoder_vault(),
+        )
+            .unwrap();
+
+        let (total_shares, _, _, sess) = helpers::get_batch_unlock_requests(sess, &ctx.vault, &batch).unwrap();
+        assert_eq!(total_shares, 6000);
+        let (token_bal, sess) = helpers::query_token_balance(sess, &ctx.share_token, &ctx.alice).unwrap();
+        assert_eq!(token_bal, 1_000_000 - 6000);
+        let (result, sess) = helpers::get_unlock_request_count(sess, &ctx.vault, &ctx.alice).unwrap();
+        assert_eq!(result, 1);
+
+        let sess = helpers::call_send_batch_unlock_requests(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            vec![batch],
+        )
+            .unwrap();
+
+        // Cannot cancel after the batch has been sent
+        match helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.alice,
+            String::from("cancel_unlock_request"),
+            Some(vec![String::from("0")]),
+            None,
+            helpers::transcoder_vault(),
+        ) {
+            Ok(_) => panic!("Should panic because the batch unlock request has been sent"),
+            Err(_) => (),
+        }
+
+        Ok(())
+    }
+    #[test]
+    fn test_send_pending_batches_flow() -> Result<(), Box<dyn Error>> {
+        let ctx = setup().unwrap();
+
+        let (_, sess) = helpers::call_stake(ctx.sess, &ctx.vault, &ctx.share_token, &ctx.alice, 1_000_000e10 as u128).unwrap();
+        let (_, sess) = helpers::call_stake(sess, &ctx.vault, &ctx.share_token, &ctx.bob, 1_000_000e10 as u128).unwrap();
+
+        // Unlock requests in two separate batches
+        let (batch_a, sess) = helpers::query_batch_id(sess, &ctx.vault).unwrap();
+        let (_, sess) = helpers::call_request_unlock(sess, &ctx.vault, &ctx.share_token, &ctx.alice, 10_000e10 as u128).unwrap();
+        let sess = helpers::update_days(sess, 2);
+        let (batch_b, sess) = helpers::query_batch_id(sess, &ctx.vault).unwrap();
+        let (_, sess) = helpers::call_request_unlock(sess, &ctx.vault, &ctx.share_token, &ctx.bob, 10_000e10 as u128).unwrap();
+
+        // Only the first batch has concluded
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.charlie,
+            String::from("send_pending_batches"),
+            None,
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let (_, _, redemption_timestamp, sess) = helpers::get_batch_unlock_requests(sess, &ctx.vault, &batch_a).unwrap();
+        assert!(redemption_timestamp.is_some(), "First batch should be sent");
+        let (_, _, redemption_timestamp, sess) = helpers::get_batch_unlock_requests(sess, &ctx.vault, &batch_b).unwrap();
+        assert!(redemption_timestamp.is_none(), "Current batch should not be sent");
+
+        // Second batch concludes
+        let sess = helpers::update_days(sess, 2);
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.charlie,
+            String::from("send_pending_batches"),
+            None,
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let (_, _, redemption_timestamp, _sess) = helpers::get_batch_unlock_requests(sess, &ctx.vault, &batch_b).unwrap();
+        assert!(redemption_timestamp.is_some(), "Second batch should be sent");
+
+        Ok(())
+    }
+    #[test]
+    fn test_lazy_batch_dispatch_on_stake() -> Result<(), Box<dyn Error>> {
+        let ctx = setup().unwrap();
+
+        let sess = helpers::call_function(
+            ctx.sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("adjust_max_batches_per_dispatch"),
+            Some(vec![String::from("1")]),
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+
+        let (_, sess) = helpers::call_stake(sess, &ctx.vault, &ctx.share_token, &ctx.alice, 1_000_000e10 as u128).unwrap();
+        let (batch, sess) = helpers::query_batch_id(sess, &ctx.vault).unwrap();
+        let (_, sess) = helpers::call_request_unlock(sess, &ctx.vault, &ctx.share_token, &ctx.alice, 10_000e10 as u128).unwrap();
+
+        // Batch concludes and is sent by the next stake
+        let sess = helpers::update_days(sess, 2);
+        let (_, sess) = helpers::call_stake(sess, &ctx.vault, &ctx.share_token, &ctx.bob, 1_000_000e10 as u128).unwrap();
+
+        let (_, _, redemption_timestamp, _sess) = helpers::get_batch_unlock_requests(sess, &ctx.vault, &batch).unwrap();
+        assert!(redemption_timestamp.is_some(), "Batch should be sent lazily");
+
+        Ok(())
+    }
+    #[test]
+    fn test_keeper_reward_for_sending_batches() -> Result<(), Box<dyn Error>> {
+        let ctx = setup().unwrap();
+
+        // 0.10% of processed AZERO
+        let sess = helpers::call_function(
+            ctx.sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("adjust_keeper_reward"),
+            Some(vec![String::from("Percentage(10)")]),
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+
+        let (_, sess) = helpers::call_stake(sess, &ctx.vault, &ctx.share_token, &ctx.alice, 1_000_000e10 as u128).unwrap();
+        let (batch, sess) = helpers::query_batch_id(sess, &ctx.vault).unwrap();
+        let (_, sess) = helpers::call_request_unlock(sess, &ctx.vault, &ctx.share_token, &ctx.alice, 10_000e10 as u128).unwrap();
+
+        let sess = helpers::update_days(sess, 2);
+        let (fees_before, sess) = helpers::get_current_virtual_shares(sess, &ctx.vault).unwrap();
+
+        let sess = helpers::call_send_batch_unlock_requests(
+            sess,
+            &ctx.vault,
+            &ctx.charlie,
+            vec![batch],
+        )
+        .unwrap();
+
+        let (reward_shares, sess) = helpers::query_token_balance(sess, &ctx.share_token, &ctx.charlie).unwrap();
+        let (reward_azero, sess) = helpers::get_azero_from_shares(sess, &ctx.vault, reward_shares).unwrap();
+        assert!(reward_azero <= 10e10 as u128 && reward_azero >= 10e10 as u128 - 1, "Keeper should receive 0.10% of the batch value");
+
+        let (fees_after, _sess) = helpers::get_current_virtual_shares(sess, &ctx.vault).unwrap();
+        assert_eq!(fees_before - fees_after, reward_shares, "Keeper reward should be funded from fees");
+
+        Ok(())
+    }
+    #[test]
+    fn test_compound_with_performance_fee() -> Result<(), Box<dyn Error>> {
+        let ctx = setup().unwrap();
+
+        // Charge 10% of compounded rewards
+        let sess = helpers::call_function(
+            ctx.sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("adjust_performance_fee"),
+            Some(vec![String::from("1000")]), // 10%
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+
+        let (_, mut sess) = helpers::call_stake(sess, &ctx.vault, &ctx.share_token, &ctx.alice, 1_000_000e10 as u128).unwrap();
+
+        // Fund nominator agents to simulate AZERO being claimed
+        let mock_reward = 10_000e10 as u128;
+        sess.chain_api().add_tokens(ctx.nominators[0].clone(), mock_reward);
+        sess.chain_api().add_tokens(ctx.nominators[1].clone(), mock_reward);
+
+        let (virtual_shares_before, sess) = helpers::get_current_virtual_shares(sess, &ctx.vault).unwrap();
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("compound"),
+            None,
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let (virtual_shares_after, sess) = helpers::get_current_virtual_shares(sess, &ctx.vault).unwrap();
+
+        // 10% of 20,000 * (100% - 0.05%) = 1,999
+        let (fee_azero, _sess) = helpers::get_azero_from_shares(sess, &ctx.vault, virtual_shares_after - virtual_shares_before).unwrap();
+        assert!(fee_azero <= 1_999e10 as u128 && fee_azero >= 1_999e10 as u128 - 1);
+
+        Ok(())
+    }
+    #[test]
+    fn test_adjust_performance_fee_panic_because_cap() {
+        let ctx = setup().unwrap();
+
+        match helpers::call_function(
+            ctx.sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("adjust_performance_fee"),
+            Some(vec![String::from("3001")]), // 30.01%
+            None,
+            helpers::transcoder_vault(),
+        ) {
+            Ok(_) => panic!("Should panic because performance fee exceeds the cap"),
+            Err(_) => (),
+        };
+    }
+    #[test]
+    fn test_withdraw_fees_split_among_recipients() {
+        let ctx = setup().unwrap();
+
+        const STAKE_AMOUNT: u128 = 10_000e10 as u128;
+        const EXPECTED_FEES: u128 = STAKE_AMOUNT * 200 / helpers::BIPS;
+
+        // Split fees 60/40 between charlie and dave
+        let sess = helpers::call_function(
+            ctx.sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("set_fee_recipients"),
+            Some(vec![format!(
+                "[FeeRecipient {{ account: {}, weight: 6000 }}, FeeRecipient {{ account: {}, weight: 4000 }}]",
+                ctx.charlie, ctx.dave,
+            )]),
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+
+        let (_, sess) = helpers::call_stake(sess, &ctx.vault, &ctx.share_token, &ctx.ed, STAKE_AMOUNT).unwrap();
+        let sess = helpers::update_in_milliseconds(sess, helpers::YEAR);
+
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("withdraw_fees"),
+            None,
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+
+        let (charlie_shares, sess) = helpers::query_token_balance(sess, &ctx.share_token, &ctx.charlie).unwrap();
+        let (dave_shares, _sess) = helpers::query_token_balance(sess, &ctx.share_token, &ctx.dave).unwrap();
+        assert_eq!(charlie_shares, EXPECTED_FEES * 6000 / helpers::BIPS);
+        assert_eq!(dave_shares, EXPECTED_FEES - charlie_shares);
+    }
+    #[test]
+    fn test_set_fee_recipients_panic_because_invalid_weights() {
+        let ctx = setup().unwrap();
+
+        match helpers::call_function(
+            ctx.sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("set_fee_recipients"),
+            Some(vec![format!(
+                "[FeeRecipient {{ account: {}, weight: 6000 }}, FeeRecipient {{ account: {}, weight: 3000 }}]",
+                ctx.charlie, ctx.dave,
+            )]),
+            None,
+            helpers::transcoder_vault(),
+        ) {
+            Ok(_) => panic!("Should panic because weights do not sum to BIPS"),
+            Err(_) => (),
+        };
+    }
+    #[test]
+    fn test_referral_rewards_flow() {
+        let ctx = setup().unwrap();
+
+        const STAKE_AMOUNT: u128 = 10_000e10 as u128;
+        const EXPECTED_FEES: u128 = STAKE_AMOUNT * 200 / helpers::BIPS;
+
+        // Redirect half of the management fee on referred sAZERO
+        let sess = helpers::call_function(
+            ctx.sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("adjust_referral_fee"),
+            Some(vec![String::from("5000")]), // 50%
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+
+        // Alice stakes 10k AZERO referred by charlie
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.alice,
+            String::from("stake_with_referral"),
+            Some(vec![ctx.charlie.to_string()]),
+            Some(STAKE_AMOUNT),
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+
+        let sess = helpers::update_in_milliseconds(sess, helpers::YEAR);
+
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.charlie,
+            String::from("claim_referral_rewards"),
+            None,
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let (referral_shares, sess) = helpers::query_token_balance(sess, &ctx.share_token, &ctx.charlie).unwrap();
+        assert_eq!(referral_shares, EXPECTED_FEES / 2);
+
+        // Owner withdraws the remaining fees
+        let (shares_before, sess) = helpers::query_token_balance(sess, &ctx.share_token, &ctx.bob).unwrap();
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("withdraw_fees"),
+            None,
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let (shares_after, _sess) = helpers::query_token_balance(sess, &ctx.share_token, &ctx.bob).unwrap();
+        assert_eq!(shares_after - shares_before, EXPECTED_FEES / 2);
+    }
+    #[test]
+    fn test_stake_with_referral_panic_because_not_registered() {
+        let ctx = setup().unwrap();
+
+        let sess = helpers::call_function(
+            ctx.sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("set_referral_whitelist_enabled"),
+            Some(vec![String::from("true")]),
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+
+        match helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.alice,
+            String::from("stake_with_referral"),
+            Some(vec![ctx.charlie.to_string()]),
+            Some(10_000e10 as u128),
+            helpers::transcoder_vault(),
+        ) {
+            Ok(_) => panic!("Should panic because referral id is not registered"),
+            Err(_) => (),
+        };
+    }
+    #[test]
+    fn test_referral_attribution_restored_on_cancel() -> Result<(), Box<dyn Error>> {
+        let ctx = setup().unwrap();
+
+        const STAKE_AMOUNT: u128 = 10_000e10 as u128;
+        const EXPECTED_FEES: u128 = STAKE_AMOUNT * 200 / helpers::BIPS;
+
+        // Redirect half of the management fee on referred sAZERO
+        let sess = helpers::call_function(
+            ctx.sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("adjust_referral_fee"),
+            Some(vec![String::from("5000")]), // 50%
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+
+        // Alice stakes 10k AZERO referred by charlie
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.alice,
+            String::from("stake_with_referral"),
+            Some(vec![ctx.charlie.to_string()]),
+            Some(STAKE_AMOUNT),
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+
+        // Alice requests an unlock of all her sAZERO and cancels it
+        let (_, sess) = helpers::call_request_unlock(sess, &ctx.vault, &ctx.share_token, &ctx.alice, STAKE_AMOUNT).unwrap();
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.alice,
+            String::from("cancel_unlock_request"),
+            Some(vec![String::from("0")]),
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+
+        let sess = helpers::update_in_milliseconds(sess, helpers::YEAR);
+
+        // Charlie still earns referral rewards on the returned sAZERO
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.charlie,
+            String::from("claim_referral_rewards"),
+            None,
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let (referral_shares, _sess) = helpers::query_token_balance(sess, &ctx.share_token, &ctx.charlie).unwrap();
+        assert_eq!(referral_shares, EXPECTED_FEES / 2);
+
+        Ok(())
+    }
+    #[test]
+    fn test_fee_change_timelock_flow() {
+        let ctx = setup().unwrap();
+
+        // 1 day delay with a maximum change of 1%
+        let sess = helpers::call_function(
+            ctx.sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("adjust_fee_change_limits"),
+            Some(vec![helpers::DAY.to_string(), String::from("0"), String::from("100")]),
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+
+        // Queue fee change from 2% to 3%
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("adjust_fee"),
+            Some(vec![String::from("300")]),
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("get_fee_percentage"),
+            None,
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let res: Result<u16, drink::errors::LangError> = sess.last_call_return().unwrap();
+        assert_eq!(res.unwrap(), 200, "Fee should not change before the delay");
+
+        // Apply after the delay
+        let sess = helpers::update_days(sess, 1);
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.alice,
+            String::from("apply_fee_change"),
+            Some(vec![String::from("Management")]),
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("get_fee_percentage"),
+            None,
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let res: Result<u16, drink::errors::LangError> = sess.last_call_return().unwrap();
+        assert_eq!(res.unwrap(), 300, "Fee should change after the delay");
+    }
+    #[test]
+    fn test_apply_fee_change_panic_because_delay() {
+        let ctx = setup().unwrap();
+        let sess = helpers::call_function(
+            ctx.sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("adjust_fee_change_limits"),
+            Some(vec![helpers::DAY.to_string(), String::from("0"), String::from("100")]),
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("adjust_fee"),
+            Some(vec![String::from("300")]),
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        match helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.alice,
+            String::from("apply_fee_change"),
+            Some(vec![String::from("Management")]),
+            None,
+            helpers::transcoder_vault(),
+        ) {
+            Ok(_) => panic!("Should panic because fee change is not effective yet"),
+            Err(_) => (),
+        };
+    }
+    #[test]
+    fn test_fee_adjustment_panic_because_change_limit() {
+        let ctx = setup().unwrap();
+        let sess = helpers::call_function(
+            ctx.sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("adjust_fee_change_limits"),
+            Some(vec![String::from("0"), String::from("0"), String::from("100")]),
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        match helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("adjust_fee"),
+            Some(vec![String::from("400")]), // 2% to 4%
+            None,
+            helpers::transcoder_vault(),
+        ) {
+            Ok(_) => panic!("Should panic because fee change exceeds the limit"),
+            Err(_) => (),
+        };
+    }
+    #[test]
+    fn test_performance_fee_change_timelock_flow() {
+        let ctx = setup().unwrap();
+
+        // 1 day delay with a maximum change of 10%
+        let sess = helpers::call_function(
+            ctx.sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("adjust_fee_change_limits"),
+            Some(vec![helpers::DAY.to_string(), String::from("0"), String::from("1000")]),
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+
+        // Queue performance fee change from 0% to 10%
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("adjust_performance_fee"),
+            Some(vec![String::from("1000")]),
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("get_performance_fee_percentage"),
+            None,
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let res: Result<u16, drink::errors::LangError> = sess.last_call_return().unwrap();
+        assert_eq!(res.unwrap(), 0, "Performance fee should not change before the delay");
+
+        // Apply after the delay
+        let sess = helpers::update_days(sess, 1);
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.alice,
+            String::from("apply_fee_change"),
+            Some(vec![String::from("Performance")]),
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("get_performance_fee_percentage"),
+            None,
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let res: Result<u16, drink::errors::LangError> = sess.last_call_return().unwrap();
+        assert_eq!(res.unwrap(), 1000, "Performance fee should change after the delay");
+    }
+    #[test]
+    fn test_instant_unlock_fee_change_panic_because_delay() {
+        let ctx = setup().unwrap();
+        let sess = helpers::call_function(
+            ctx.sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("adjust_fee_change_limits"),
+            Some(vec![helpers::DAY.to_string(), String::from("0"), String::from("100")]),
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("adjust_instant_unlock_fee"),
+            Some(vec![String::from("100")]),
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        match helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.alice,
+            String::from("apply_fee_change"),
+            Some(vec![String::from("InstantUnlock")]),
+            None,
+            helpers::transcoder_vault(),
+        ) {
+            Ok(_) => panic!("Should panic because instant unlock fee change is not effective yet"),
+            Err(_) => (),
+        };
+    }
+    #[test]
+    fn test_fee_change_limits_loosening_timelock() {
+        let ctx = setup().unwrap();
+
+        // 1 day delay with a maximum change of 1%
+        let sess = helpers::call_function(
+            ctx.sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("adjust_fee_change_limits"),
+            Some(vec![helpers::DAY.to_string(), String::from("0"), String::from("100")]),
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+
+        // Removing the delay is queued behind the current delay
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("adjust_fee_change_limits"),
+            Some(vec![String::from("0"), String::from("0"), String::from("100")]),
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("get_fee_change_limits"),
+            None,
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let res: Result<(u64, u64, u16), drink::errors::LangError> = sess.last_call_return().unwrap();
+        assert_eq!(res.unwrap(), (helpers::DAY, 0, 100), "Limits should not loosen before the delay");
+
+        // Apply after the delay
+        let sess = helpers::update_days(sess, 1);
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.alice,
+            String::from("apply_fee_change_limits"),
+            None,
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("get_fee_change_limits"),
+            None,
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let res: Result<(u64, u64, u16), drink::errors::LangError> = sess.last_call_return().unwrap();
+        assert_eq!(res.unwrap(), (0, 0, 100));
+    }
+    #[test]
+    fn test_rate_twap_flow() {
+        let ctx = setup().unwrap();
+
+        let (_, sess) = helpers::call_stake(ctx.sess, &ctx.vault, &ctx.share_token, &ctx.alice, 1_000_000e10 as u128).unwrap();
+        let sess = helpers::update_days(sess, 2);
+        let (_, sess) = helpers::call_stake(sess, &ctx.vault, &ctx.share_token, &ctx.bob, 1_000_000e10 as u128).unwrap();
+
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("get_rate"),
+            None,
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let rate: Result<u128, drink::errors::LangError> = sess.last_call_return().unwrap();
+        let rate = rate.unwrap();
+        assert!(rate < 1e12 as u128, "Rate should reflect accrued fees");
+
+        // Window covered by the checkpoint of the initial stake
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("get_twap"),
+            Some(vec![helpers::DAY.to_string()]),
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let twap: Result<Option<u128>, drink::errors::LangError> = sess.last_call_return().unwrap();
+        assert_eq!(twap.unwrap(), Some(1e12 as u128), "Initial checkpoint rate should hold until the next checkpoint");
+
+        // Window precedes all checkpoints
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("get_twap"),
+            Some(vec![(helpers::DAY * 3).to_string()]),
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let twap: Result<Option<u128>, drink::errors::LangError> = sess.last_call_return().unwrap();
+        assert_eq!(twap.unwrap(), None);
+    }
+    #[test]
+    fn test_estimated_apy_after_compound() -> Result<(), Box<dyn Error>> {
+        let ctx = setup().unwrap();
+
+        let (_, mut sess) = helpers::call_stake(ctx.sess, &ctx.vault, &ctx.share_token, &ctx.alice, 1_000_000e10 as u128).unwrap();
+
+        // Fund nominator agents to simulate AZERO being claimed
+        let mock_reward = 10_000e10 as u128;
+        sess.chain_api().add_tokens(ctx.nominators[0].clone(), mock_reward);
+        sess.chain_api().add_tokens(ctx.nominators[1].clone(), mock_reward);
+
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("compound"),
+            None,
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("get_estimated_apy"),
+            Some(vec![helpers::DAY.to_string()]),
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let estimate: Result<helpers::ApyEstimate, drink::errors::LangError> = sess.last_call_return().unwrap();
+        let estimate = estimate.unwrap();
+
+        // 19,990 AZERO compounded on 1m AZERO within 1 day annualized
+        assert_eq!(estimate.gross_apy, 73013);
+        assert_eq!(estimate.net_apy, 73013 - 200);
+        assert_eq!(estimate.agents.len(), 2);
+        assert_eq!(estimate.agents[0].apy, 73013);
+
+        Ok(())
+    }
+    #[test]
+    fn test_stake_panic_because_max_total_pooled() {
+        let ctx = setup().unwrap();
+
+        let sess = helpers::call_function(
+            ctx.sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("adjust_max_total_pooled"),
+            Some(vec![(1_500_000e10 as u128).to_string()]),
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+
+        let (_, sess) = helpers::call_stake(sess, &ctx.vault, &ctx.share_token, &ctx.alice, 1_000_000e10 as u128).unwrap();
+        match helpers::call_stake(sess, &ctx.vault, &ctx.share_token, &ctx.bob, 1_000_000e10 as u128) {
+            Ok(_) => panic!("Should panic because stake exceeds the maximum total pooled"),
+            Err(_) => (),
+        };
+    }
+    #[test]
+    fn test_stake_panic_because_max_account_stake() {
+        let ctx = setup().unwrap();
+
+        let sess = helpers::call_function(
+            ctx.sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("adjust_max_account_stake"),
+            Some(vec![format!("Some({})", 1_500_000e10 as u128)]),
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+
+        let (_, sess) = helpers::call_stake(sess, &ctx.vault, &ctx.share_token, &ctx.alice, 1_000_000e10 as u128).unwrap();
+        let (_, sess) = helpers::call_stake(sess, &ctx.vault, &ctx.share_token, &ctx.bob, 1_000_000e10 as u128).unwrap();
+        match helpers::call_stake(sess, &ctx.vault, &ctx.share_token, &ctx.alice, 1_000_000e10 as u128) {
+            Ok(_) => panic!("Should panic because stake exceeds the maximum account stake"),
+            Err(_) => (),
+        };
+    }
+    #[test]
+    fn test_allowlist_flow() {
+        let ctx = setup().unwrap();
+
+        let sess = helpers::call_function(
+            ctx.sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("set_allowlist_enabled"),
+            Some(vec![String::from("true")]),
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("add_to_allowlist"),
+            Some(vec![format!("[{}, {}]", ctx.alice, ctx.charlie)]),
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("is_allowlisted"),
+            Some(vec![ctx.alice.to_string()]),
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let res: Result<bool, drink::errors::LangError> = sess.last_call_return().unwrap();
+        assert!(res.unwrap());
+
+        let (_, sess) = helpers::call_stake(sess, &ctx.vault, &ctx.share_token, &ctx.alice, 1_000_000e10 as u128).unwrap();
+        match helpers::call_stake(sess, &ctx.vault, &ctx.share_token, &ctx.dave, 1_000_000e10 as u128) {
+            Ok(_) => panic!("Should panic because staker is not allowlisted"),
+            Err(_) => (),
+        };
+    }
+    #[test]
+    fn test_pause_staking_flow() {
+        let ctx = setup().unwrap();
+
+        let sess = helpers::call_function(
+            ctx.sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("set_paused"),
+            Some(vec![String::from("Staking"), String::from("true")]),
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("set_paused"),
+            Some(vec![String::from("Staking"), String::from("false")]),
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let (_, sess) = helpers::call_stake(sess, &ctx.vault, &ctx.share_token, &ctx.alice, 1_000_000e10 as u128).unwrap();
+
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("set_paused"),
+            Some(vec![String::from("Staking"), String::from("true")]),
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        match helpers::call_stake(sess, &ctx.vault, &ctx.share_token, &ctx.alice, 1_000_000e10 as u128) {
+            Ok(_) => panic!("Should panic because staking is paused"),
+            Err(_) => (),
+        };
+    }
+    #[test]
+    fn test_pause_panic_because_caller_restricted() {
+        let ctx = setup().unwrap();
+
+        match helpers::call_function(
+            ctx.sess,
+            &ctx.vault,
+            &ctx.ed, // not bob
+            String::from("set_paused"),
+            Some(vec![String::from("Compounding"), String::from("true")]),
+            None,
+            helpers::transcoder_vault(),
+        ) {
+            Ok(_) => panic!("Should panic because caller is not the pauser (Bob)"),
+            Err(_) => (),
+        };
+    }
+    #[test]
+    fn test_sync_pool_values_without_loss() -> Result<(), Box<dyn Error>> {
+        let ctx = setup().unwrap();
+
+        let (_, sess) = helpers::call_stake(ctx.sess, &ctx.vault, &ctx.share_token, &ctx.alice, 1_000_000e10 as u128).unwrap();
+
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("sync_pool_values"),
+            None,
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+
+        // Agents report the expected bonded AZERO
+        let (total_pooled, _sess) = helpers::get_total_pooled(sess, &ctx.vault).unwrap();
+        assert_eq!(total_pooled, 1_000_000e10 as u128);
+
+        Ok(())
+    }
+    #[test]
+    fn test_sync_pool_values_with_loss() -> Result<(), Box<dyn Error>> {
+        let ctx = setup().unwrap();
+
+        let (_, sess) = helpers::call_stake(ctx.sess, &ctx.vault, &ctx.share_token, &ctx.alice, 1_000_000e10 as u128).unwrap();
+
+        // Record the expected pool balances
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("sync_pool_values"),
+            None,
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let (total_pooled_before, sess) = helpers::get_total_pooled(sess, &ctx.vault).unwrap();
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("get_rate"),
+            None,
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let rate_before: Result<u128, drink::errors::LangError> = sess.last_call_return().unwrap();
+
+        // Owner reports a slash of the first agent's pool
+        let loss = 10_000e10 as u128;
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("report_agent_loss"),
+            Some(vec![ctx.nominators[0].to_string(), loss.to_string()]),
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("sync_pool_values"),
+            None,
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+
+        // Detected loss is socialized through the redemption ratio
+        let (total_pooled_after, sess) = helpers::get_total_pooled(sess, &ctx.vault).unwrap();
+        assert_eq!(total_pooled_after, total_pooled_before - loss);
+
+        // Loss is only detected once
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("sync_pool_values"),
+            None,
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let (total_pooled_after, sess) = helpers::get_total_pooled(sess, &ctx.vault).unwrap();
+        assert_eq!(total_pooled_after, total_pooled_before - loss);
+
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("get_rate"),
+            None,
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let rate_after: Result<u128, drink::errors::LangError> = sess.last_call_return().unwrap();
+        assert!(rate_after.unwrap() < rate_before.unwrap(), "Rate should drop after the loss");
+
+        // Correcting the report recovers the pooled AZERO
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("report_agent_loss"),
+            Some(vec![ctx.nominators[0].to_string(), 0.to_string()]),
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("sync_pool_values"),
+            None,
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let (total_pooled_after, _sess) = helpers::get_total_pooled(sess, &ctx.vault).unwrap();
+        assert_eq!(total_pooled_after, total_pooled_before);
+
+        Ok(())
+    }
+    #[test]
+    fn test_sync_pool_values_guardian_pause() {
+        let ctx = setup().unwrap();
+
+        let (_, sess) = helpers::call_stake(ctx.sess, &ctx.vault, &ctx.share_token, &ctx.alice, 1_000_000e10 as u128).unwrap();
+
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("sync_pool_values"),
+            None,
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+
+        // Loss beyond the tolerance pauses staking
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("report_agent_loss"),
+            Some(vec![ctx.nominators[0].to_string(), (10_000e10 as u128).to_string()]),
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.alice,
+            String::from("sync_pool_values"),
+            None,
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        match helpers::call_stake(sess, &ctx.vault, &ctx.share_token, &ctx.alice, 1_000e10 as u128) {
+            Ok(_) => panic!("Should panic because staking is paused by the guardian"),
+            Err(_) => (),
+        };
+    }
+    #[test]
+    fn test_compound_guardian_pause_flow() {
+        let ctx = setup().unwrap();
+
+        let (_, mut sess) = helpers::call_stake(ctx.sess, &ctx.vault, &ctx.share_token, &ctx.alice, 1_000_000e10 as u128).unwrap();
+
+        // Dust loss within the tolerance does not pause the vault
+        sess.chain_api().add_tokens(ctx.nominators[0].clone(), 10_000e10 as u128);
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("report_agent_loss"),
+            Some(vec![ctx.nominators[0].to_string(), (1e10 as u128).to_string()]),
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("compound"),
+            None,
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let (_, mut sess) = helpers::call_stake(sess, &ctx.vault, &ctx.share_token, &ctx.alice, 1_000e10 as u128).unwrap();
+
+        // Loss beyond the tolerance pauses staking
+        sess.chain_api().add_tokens(ctx.nominators[0].clone(), 10_000e10 as u128);
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("report_agent_loss"),
+            Some(vec![ctx.nominators[0].to_string(), (10_000e10 as u128).to_string()]),
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("compound"),
+            None,
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        match helpers::call_stake(sess, &ctx.vault, &ctx.share_token, &ctx.alice, 1_000e10 as u128) {
+            Ok(_) => panic!("Should panic because staking is paused by the guardian"),
+            Err(_) => (),
+        };
+    }
+    #[test]
+    fn test_insurance_fund_cover_loss_flow() -> Result<(), Box<dyn Error>> {
+        let ctx = setup().unwrap();
+
+        let sess = helpers::call_function(
+            ctx.sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("adjust_insurance_percentages"),
+            Some(vec![0.to_string(), 100_00.to_string()]),
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+
+        let (_, mut sess) = helpers::call_stake(sess, &ctx.vault, &ctx.share_token, &ctx.alice, 1_000_000e10 as u128).unwrap();
+
+        // Fund nominator agents to simulate AZERO being claimed
+        let mock_reward = 10_000e10 as u128;
+        sess.chain_api().add_tokens(ctx.nominators[0].clone(), mock_reward);
+        sess.chain_api().add_tokens(ctx.nominators[1].clone(), mock_reward);
+
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("compound"),
+            None,
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let (total_pooled_before, sess) = helpers::get_total_pooled(sess, &ctx.vault).unwrap();
+
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("get_insurance_balance"),
+            None,
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let balance: Result<(u128, u128), drink::errors::LangError> = sess.last_call_return().unwrap();
+        let (insurance_shares, insurance_azero) = balance.unwrap();
+
+        // Entire compounding incentive is retained by the insurance fund
+        assert_eq!(insurance_shares, 0);
+        assert!(insurance_azero > 0);
+
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("cover_loss"),
+            Some(vec![insurance_azero.to_string()]),
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+
+        // Insurance AZERO is bonded without minting sAZERO
+        let (total_pooled, _sess) = helpers::get_total_pooled(sess, &ctx.vault).unwrap();
+        assert_eq!(total_pooled, total_pooled_before + insurance_azero);
+
+        Ok(())
+    }
+    #[test]
+    fn test_cover_loss_panic_because_insufficient_insurance() {
+        let ctx = setup().unwrap();
+
+        let (_, sess) = helpers::call_stake(ctx.sess, &ctx.vault, &ctx.share_token, &ctx.alice, 1_000_000e10 as u128).unwrap();
+
+        match helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("cover_loss"),
+            Some(vec![(1e10 as u128).to_string()]),
+            None,
+            helpers::transcoder_vault(),
+        ) {
+            Ok(_) => panic!("Should panic because the insurance fund is empty"),
+            Err(_) => (),
+        };
+    }
+    #[test]
+    fn test_cover_loss_panic_because_zero_loss() {
+        let ctx = setup().unwrap();
+
+        let (_, sess) = helpers::call_stake(ctx.sess, &ctx.vault, &ctx.share_token, &ctx.alice, 1_000_000e10 as u128).unwrap();
+
+        match helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("cover_loss"),
+            Some(vec![0.to_string()]),
+            None,
+            helpers::transcoder_vault(),
+        ) {
+            Ok(_) => panic!("Should panic because the loss is zero"),
+            Err(_) => (),
+        };
+    }
+    #[test]
+    fn test_donate_streamed_flow() -> Result<(), Box<dyn Error>> {
+        let ctx = setup().unwrap();
+
+        let (_, sess) = helpers::call_stake(ctx.sess, &ctx.vault, &ctx.share_token, &ctx.alice, 1_000_000e10 as u128).unwrap();
+
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("adjust_donation_duration"),
+            Some(vec![helpers::DAY.to_string()]),
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+
+        let donation = 10_000e10 as u128;
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("donate"),
+            None,
+            Some(donation),
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+
+        // Donated AZERO is bonded but withheld from the redemption ratio
+        let (total_pooled, sess) = helpers::get_total_pooled(sess, &ctx.vault).unwrap();
+        assert_eq!(total_pooled, 1_000_000e10 as u128);
+
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("get_unreleased_donations"),
+            None,
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let unreleased: Result<u128, drink::errors::LangError> = sess.last_call_return().unwrap();
+        assert_eq!(unreleased.unwrap(), donation);
+
+        // Full donation is released after the duration
+        let sess = helpers::update_days(sess, 1);
+        let (_, sess) = helpers::call_stake(sess, &ctx.vault, &ctx.share_token, &ctx.alice, 1_000e10 as u128).unwrap();
+
+        let (total_pooled, _sess) = helpers::get_total_pooled(sess, &ctx.vault).unwrap();
+        assert_eq!(total_pooled, 1_000_000e10 as u128 + donation + 1_000e10 as u128);
+
+        Ok(())
+    }
+    #[test]
+    fn test_donate_panic_because_zero_shares() {
+        let ctx = setup().unwrap();
+
+        match helpers::call_function(
+            ctx.sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("donate"),
+            None,
+            Some(10_000e10 as u128),
+            helpers::transcoder_vault(),
+        ) {
+            Ok(_) => panic!("Should panic because no sAZERO exists"),
+            Err(_) => (),
+        };
+    }
+    #[test]
+    fn test_compound_rewards_vesting_flow() -> Result<(), Box<dyn Error>> {
+        let ctx = setup().unwrap();
+
+        let sess = helpers::call_function(
+            ctx.sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("adjust_reward_vesting_period"),
+            Some(vec![helpers::DAY.to_string()]),
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+
+        let (_, mut sess) = helpers::call_stake(sess, &ctx.vault, &ctx.share_token, &ctx.alice, 1_000_000e10 as u128).unwrap();
+
+        // Fund nominator agents to simulate AZERO being claimed
+        let mock_reward = 10_000e10 as u128;
+        sess.chain_api().add_tokens(ctx.nominators[0].clone(), mock_reward);
+        sess.chain_api().add_tokens(ctx.nominators[1].clone(), mock_reward);
+
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("compound"),
+            None,
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+
+        // Compounded AZERO is not reflected in the redemption ratio yet
+        let (total_pooled, sess) = helpers::get_total_pooled(sess, &ctx.vault).unwrap();
+        assert_eq!(total_pooled, 1_000_000e10 as u128);
+
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("get_unvested_rewards"),
+            None,
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let unvested: Result<u128, drink::errors::LangError> = sess.last_call_return().unwrap();
+        let unvested = unvested.unwrap();
+        assert!(unvested > 0);
+
+        // Half of the rewards vest after half of the vesting period
+        let sess = helpers::update_in_milliseconds(sess, helpers::DAY / 2);
+        let (total_pooled, sess) = helpers::get_total_pooled(sess, &ctx.vault).unwrap();
+        assert_eq!(total_pooled, 1_000_000e10 as u128 + unvested / 2);
+
+        // All rewards vest after the vesting period
+        let sess = helpers::update_in_milliseconds(sess, helpers::DAY / 2);
+        let (total_pooled, _sess) = helpers::get_total_pooled(sess, &ctx.vault).unwrap();
+        assert_eq!(total_pooled, 1_000_000e10 as u128 + unvested);
+
+        Ok(())
+    }
+    #[test]
+    fn test_donate_streamed_rate_and_weighted_end() -> Result<(), Box<dyn Error>> {
+        let ctx = setup().unwrap();
+
+        let (_, sess) = helpers::call_stake(ctx.sess, &ctx.vault, &ctx.share_token, &ctx.alice, 1_000_000e10 as u128).unwrap();
+
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("adjust_donation_duration"),
+            Some(vec![helpers::DAY.to_string()]),
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("donate"),
+            None,
+            Some(10_000e10 as u128),
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+
+        // Rate queries reflect released donations without a state change
+        let sess = helpers::update_in_milliseconds(sess, helpers::DAY / 2);
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("get_rate"),
+            None,
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let rate: Result<u128, drink::errors::LangError> = sess.last_call_return().unwrap();
+        assert!(rate.unwrap() > 1_004_000_000_000, "Rate should include half of the donation");
+
+        // A dust donation barely delays the release of the remaining donation
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("donate"),
+            None,
+            Some(1e10 as u128),
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let sess = helpers::update_in_milliseconds(sess, helpers::DAY / 2);
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("get_unreleased_donations"),
+            None,
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let unreleased: Result<u128, drink::errors::LangError> = sess.last_call_return().unwrap();
+        assert!(unreleased.unwrap() < 2e10 as u128, "Dust donation should not restart the stream");
+
+        Ok(())
+    }
+    #[test]
+    fn test_vault_transfer_role_admin_flow() {
+        let ctx = setup().unwrap();
+
+        // Propose administration of the fee split role to Charlie
+        let sess = helpers::call_function(
+            ctx.sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("propose_role_admin_transfer"),
+            Some([String::from("FeeSplit"), ctx.charlie.to_string()].to_vec()),
+            None,
+            helpers::transcoder_vault(),
+        )
+            .unwrap();
+
+        // Administration is not transferred until accepted
+        let (admin, sess) = helpers::get_vault_role_admin(sess, &ctx.vault, "FeeSplit").unwrap();
+        assert_eq!(admin, ctx.bob);
+
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.charlie,
+            String::from("accept_role_admin"),
+            Some([String::from("FeeSplit")].to_vec()),
+            None,
+            helpers::transcoder_vault(),
+        )
+            .unwrap();
+        let (admin, sess) = helpers::get_vault_role_admin(sess, &ctx.vault, "FeeSplit").unwrap();
+        assert_eq!(admin, ctx.charlie);
+
+        // New admin can propose the role
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.charlie,
+            String::from("propose_role_transfer"),
+            Some([String::from("FeeSplit"), ctx.dave.to_string()].to_vec()),
+            None,
+            helpers::transcoder_vault(),
+        )
+            .unwrap();
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.dave,
+            String::from("accept_role"),
+            Some([String::from("FeeSplit")].to_vec()),
+            None,
+            helpers::transcoder_vault(),
+        )
+            .unwrap();
+        let (account, _sess) = helpers::get_vault_role(sess, &ctx.vault, "FeeSplit").unwrap();
+        assert_eq!(account, ctx.dave);
+    }
+    #[test]
+    fn test_vault_cancel_role_transfer_flow() {
+        let ctx = setup().unwrap();
+
+        let sess = helpers::call_function(
+            ctx.sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("propose_role_transfer"),
+            Some([String::from("Pauser"), ctx.charlie.to_string()].to_vec()),
+            None,
+            helpers::transcoder_vault(),
+        )
+            .unwrap();
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("propose_role_admin_transfer"),
+            Some([String::from("Pauser"), ctx.dave.to_string()].to_vec()),
+            None,
+            helpers::transcoder_vault(),
+        )
+            .unwrap();
+
+        let (pending, sess) = helpers::get_pending_role_transfers(sess, &ctx.vault).unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].0, helpers::VaultRoleType::Pauser);
+        assert_eq!(pending[0].1.account, Some(ctx.charlie.clone()));
+        assert_eq!(pending[0].1.admin, Some(ctx.dave.clone()));
+
+        // Cancelling clears both pending transfers
+        let sess = helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("cancel_role_transfer"),
+            Some([String::from("Pauser")].to_vec()),
+            None,
+            helpers::transcoder_vault(),
+        )
+            .unwrap();
+        let (pending, sess) = helpers::get_pending_role_transfers(sess, &ctx.vault).unwrap();
+        assert_eq!(pending.len(), 0);
+
+        match helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.charlie,
+            String::from("accept_role"),
+            Some([String::from("Pauser")].to_vec()),
+            None,
+            helpers::transcoder_vault(),
+        ) {
+            Ok(_) => panic!("Should panic because the role transfer was cancelled"),
+            Err(_) => (),
+        };
+    }
+    #[test]
+    fn test_vault_renounce_role_flow() {
+        let ctx = setup().unwrap();
+
+        let sess = helpers::call_function(
+            ctx.sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("renounce_role"),
+            Some([String::from("Pauser")].to_vec()),
+            None,
+            helpers::transcoder_vault(),
+        )
+            .unwrap();
+        let (account, sess) = helpers::get_vault_role(sess, &ctx.vault, "Pauser").unwrap();
+        assert_eq!(account, AccountId32::new([0u8; 32]));
+
+        // Admin is retained and can assign the role again
+        let (admin, sess) = helpers::get_vault_role_admin(sess, &ctx.vault, "Pauser").unwrap();
+        assert_eq!(admin, ctx.bob);
+
+        match helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("set_paused"),
+            Some([String::from("Staking"), String::from("true")].to_vec()),
+            None,
+            helpers::transcoder_vault(),
+        ) {
+            Ok(_) => panic!("Should panic because the pauser role was renounced"),
+            Err(_) => (),
+        };
+    }
+    #[test]
+    fn test_vault_renounce_role_admin_flow() {
+        let ctx = setup().unwrap();
+
+        let sess = helpers::call_function(
+            ctx.sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("renounce_role_admin"),
+            Some([String::from("Allowlist")].to_vec()),
+            None,
+            helpers::transcoder_vault(),
+        )
+            .unwrap();
+        let (admin, sess) = helpers::get_vault_role_admin(sess, &ctx.vault, "Allowlist").unwrap();
+        assert_eq!(admin, AccountId32::new([0u8; 32]));
+
+        // Role account is frozen
+        let (account, sess) = helpers::get_vault_role(sess, &ctx.vault, "Allowlist").unwrap();
+        assert_eq!(account, ctx.bob);
+
+        match helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("propose_role_transfer"),
+            Some([String::from("Allowlist"), ctx.charlie.to_string()].to_vec()),
+            None,
+            helpers::transcoder_vault(),
+        ) {
+            Ok(_) => panic!("Should panic because administration of the role was renounced"),
+            Err(_) => (),
+        };
+    }
+    #[test]
+    fn test_apply_code_panic_because_delay() {
+        let ctx = setup().unwrap();
+
+        let sess = helpers::call_function(
+            ctx.sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("propose_code"),
+            Some(vec![format!("{:?}", [1u8; 32]), helpers::DAY.to_string()]),
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+
+        match helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("apply_code"),
+            None,
+            None,
+            helpers::transcoder_vault(),
+        ) {
+            Ok(_) => panic!("Should panic because the code delay has not elapsed"),
+            Err(_) => (),
+        };
+    }
+    #[test]
+    fn test_migrate_panic_because_invalid_version() {
+        let ctx = setup().unwrap();
+
+        let sess = helpers::call_function(
+            ctx.sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("get_version"),
+            None,
+            None,
+            helpers::transcoder_vault(),
+        )
+        .unwrap();
+        let version: Result<u32, drink::errors::LangError> = sess.last_call_return().unwrap();
+        assert_eq!(version.unwrap(), 1);
+
+        match helpers::call_function(
+            sess,
+            &ctx.vault,
+            &ctx.bob,
+            String::from("migrate"),
+            Some(vec![2.to_string()]),
+            None,
+            helpers::transcoder_vault(),
+        ) {
+            Ok(_) => panic!("Should panic because no migration to version 2 exists"),
+            Err(_) => (),
+        };
+    }
+    #[test]
+    fn test_upgrade_panic_because_baseline_layout() {
+        let ctx = setup().unwrap();
+        let mut sess = ctx.sess;
+
+        // Vault deployed before versioned storage
+        let code_hash = sess.upload(helpers::bytes_vault()).expect("Session should upload vault bytes");
+        let vault_v0 = sess.deploy(
+            helpers::bytes_vault_v0(),
+            "new",
+            &[
+                helpers::hash_share_token(),
+                helpers::hash_registry(),
+                helpers::hash_nominator(),
+            ],
+            vec![2],
+            None,
+            &helpers::transcoder_vault_v0().unwrap(),
+        )
+        .unwrap();
+
+        let sess = helpers::call_function(
+            sess,
+            &vault_v0,
+            &ctx.bob,
+            String::from("set_code"),
+            Some(vec![format!("{:?}", code_hash.0)]),
+            None,
+            helpers::transcoder_vault_v0(),
+        )
+        .unwrap();
+
+        // Baseline storage layout cannot be decoded by the upgraded code
+        match helpers::call_function(
+            sess,
+            &vault_v0,
+            &ctx.bob,
+            String::from("get_version"),
+            None,
+            None,
+            helpers::transcoder_vault(),
+        ) {
+            Ok(_) => panic!("Should panic because baseline vaults must be redeployed"),
+            Err(_) => (),
+        };
+    }
 }
@@ -60,6 +60,14 @@ pub fn transcoder_vault() -> Option<Rc<ContractMessageTranscoder>> {
             .expect("Failed to create transcoder"),
     ))
 }
+pub fn transcoder_vault_v0() -> Option<Rc<ContractMessageTranscoder>> {
+    Some(Rc::new(
+        ContractMessageTranscoder::load(PathBuf::from(
+            "../deployments/vault_v0/vault.json",
+        ))
+            .expect("Failed to create transcoder"),
+    ))
+}
 pub fn transcoder_nominator() -> Option<Rc<ContractMessageTranscoder>> {
     Some(Rc::new(
         ContractMessageTranscoder::load(PathBuf::from(
@@ -83,6 +91,10 @@ pub fn bytes_vault() -> Vec<u8> {
     read("../deployments/vault/vault.wasm")
         .expect("Failed to find or read contract file")
 }
+pub fn bytes_vault_v0() -> Vec<u8> {
+    read("../deployments/vault_v0/vault.wasm")
+        .expect("Failed to find or read contract file")
+}
 pub fn bytes_nominator() -> Vec<u8> {
     read("../deployments/mock_nominator/mock_nominator.wasm")
         .expect("Failed to find or read contract file")
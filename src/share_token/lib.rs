@@ -40,6 +40,23 @@ mod token {
             self.emit_events(events);
             Ok(())
         }
+        /// Transfers on behalf of `spender` consuming the allowance `from` has granted to `spender`
+        /// Allows the operator to act for an approved third party rather than bypassing allowances
+        #[ink(message, selector = 7778)]
+        pub fn spender_transfer_from(
+            &mut self,
+            spender: AccountId,
+            from: AccountId,
+            to: AccountId,
+            value: u128,
+        ) -> Result<(), PSP22Error> {
+            if Self::env().caller() != self.operator {
+                return Err(PSP22Error::Custom(String::from("Caller is not Operator")));
+            }
+            let events = self.data.transfer_from(spender, from, to, value)?;
+            self.emit_events(events);
+            Ok(())
+        }
         #[ink(message)]
         pub fn get_owner(&self) -> AccountId {
             self.owner
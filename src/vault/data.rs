@@ -9,6 +9,8 @@ use crate::nomination_agent_utils::{
 use ink::{
     env::{
         debug_println,
+        hash::Blake2x256,
+        hash_bytes,
         DefaultEnvironment,
         Environment,
     },
@@ -26,13 +28,43 @@ type Timestamp = u64;
 pub const BIPS: u16 = 10000;
 pub const DAY: u64 = 86400 * 1000;
 pub const YEAR: u64 = DAY * 365_25 / 100; // https://docs.alephzero.org/aleph-zero/use/stake/staking-rewards
+pub const MAX_BATCH_SCAN: u64 = 64;
+pub const MAX_PERFORMANCE_FEE: u16 = 30_00; // 30.00%
+pub const REFERRAL_PRECISION: u128 = 1_000_000_000_000;
+pub const MAX_FEE: u16 = 10_00; // 10.00%
+pub const MAX_INCENTIVE: u16 = 1_00; // 1.00%
+pub const MAX_RATE_CHECKPOINTS: u32 = 256;
+pub const RATE_PRECISION: u128 = 1_000_000_000_000;
+pub const MAX_COMPOUND_RECORDS: u32 = 64;
+pub const GUARDIAN_LOSS_TOLERANCE: u16 = 1; // 0.01%
+pub const MIN_CODE_DELAY: u64 = DAY;
+/// Ordered storage migrations, `MIGRATIONS[i]` migrates `VaultData` from version `i + 1` to version `i + 2`
+///
+/// Packed fields of `VaultData` are decoded before any message runs, so their encoding must not change between versions
+/// State added or changed by an upgrade lives in `Mapping` or `Lazy` fields which are stored under their own keys,
+/// migrations convert entries stored with a previous encoding by reading them via `ink::env::take_contract_storage()`
+///
+/// Vaults deployed before version 1 store `VaultData` in a packed layout which cannot be decoded by version 1,
+/// they cannot be upgraded and must be replaced by a fresh deployment
+pub const MIGRATIONS: &[fn(&mut VaultData)] = &[];
+pub const VAULT_VERSION: u32 = MIGRATIONS.len() as u32 + 1;
+pub const ROLE_TYPES: [RoleType; 5] = [
+    RoleType::Owner,
+    RoleType::AdjustFee,
+    RoleType::FeeSplit,
+    RoleType::Allowlist,
+    RoleType::Pauser,
+];
 
 #[derive(Debug, PartialEq, Eq, Clone, scale::Encode, scale::Decode)]
 #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
 pub struct UnlockRequest {
+    pub owner: AccountId,
     pub creation_time: Timestamp,
     pub share_amount: Balance,
     pub batch_id: u64,
+    /// account approved to transfer the unlock request on behalf of its owner
+    pub operator: Option<AccountId>,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, scale::Encode, scale::Decode)]
@@ -43,6 +75,282 @@ pub struct UnlockRequestBatch {
     pub redemption_timestamp: Option<Timestamp>,
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+pub struct RateCheckpoint {
+    pub timestamp: Timestamp,
+    /// total AZERO backing sAZERO
+    pub total_pooled: Balance,
+    /// sAZERO supply including virtual shares
+    pub total_shares: Balance,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+pub struct AgentCompound {
+    pub agent: AccountId,
+    pub compounded: Balance,
+    /// AZERO staked by the agent before compounding
+    pub staked: Balance,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+pub struct CompoundRecord {
+    pub timestamp: Timestamp,
+    pub compounded: Balance,
+    /// total AZERO staked before compounding
+    pub total_pooled: Balance,
+    pub agents: Vec<AgentCompound>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct AgentLoss {
+    pub agent: AccountId,
+    /// AZERO the agent was expected to have bonded
+    pub expected: Balance,
+    /// AZERO held by the agent's nomination pool
+    pub actual: Balance,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct AgentApy {
+    pub agent: AccountId,
+    /// annualized realized yield expressed in basis points
+    pub apy: u32,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct ApyEstimate {
+    /// annualized compounded yield expressed in basis points
+    pub gross_apy: u32,
+    /// gross yield less the performance fee and the management fee expressed in basis points
+    pub net_apy: u32,
+    pub agents: Vec<AgentApy>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+pub struct CodeProposal {
+    pub code_hash: [u8; 32],
+    /// time after which the code can be applied
+    pub effective_time: Timestamp,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+pub enum RoleType {
+    // Permission to withdraw fees, adjust Vault parameters, and upgrade the Vault
+    Owner,
+    // Permission to adjust fees
+    AdjustFee,
+    // Permission to manage the fee split table
+    FeeSplit,
+    // Permission to manage the staking allowlist
+    Allowlist,
+    // Permission to pause and unpause Vault operations
+    Pauser,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+pub struct Role {
+    /// account that can transfer the role
+    pub admin: AccountId,
+    /// account that holds the role
+    pub account: AccountId,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+pub struct PendingRole {
+    /// proposed account which must accept the role
+    pub account: Option<AccountId>,
+    /// proposed admin which must accept administration of the role
+    pub admin: Option<AccountId>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum PauseSwitch {
+    Staking,
+    UnlockRequests,
+    BatchSending,
+    Redemption,
+    Compounding,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+pub struct PauseState {
+    pub staking: bool,
+    pub unlock_requests: bool,
+    pub batch_sending: bool,
+    pub redemption: bool,
+    pub compounding: bool,
+}
+
+impl PauseState {
+    pub fn get(&self, switch: PauseSwitch) -> bool {
+        match switch {
+            PauseSwitch::Staking => self.staking,
+            PauseSwitch::UnlockRequests => self.unlock_requests,
+            PauseSwitch::BatchSending => self.batch_sending,
+            PauseSwitch::Redemption => self.redemption,
+            PauseSwitch::Compounding => self.compounding,
+        }
+    }
+
+    pub fn set(&mut self, switch: PauseSwitch, paused: bool) {
+        match switch {
+            PauseSwitch::Staking => self.staking = paused,
+            PauseSwitch::UnlockRequests => self.unlock_requests = paused,
+            PauseSwitch::BatchSending => self.batch_sending = paused,
+            PauseSwitch::Redemption => self.redemption = paused,
+            PauseSwitch::Compounding => self.compounding = paused,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+pub struct ReleaseStream {
+    /// AZERO not yet released
+    pub remaining: Balance,
+    /// time of the last release
+    pub last_update: Timestamp,
+    /// time at which all remaining AZERO is released
+    pub end: Timestamp,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+pub enum FeeKind {
+    // Annualized management fee (`fee_percentage`)
+    Management,
+    // Compounding incentive (`incentive_percentage`)
+    Incentive,
+    // Performance fee on compounded rewards (`performance_fee_percentage`)
+    Performance,
+    // Instant unlock fee (`instant_unlock_fee_percentage`)
+    InstantUnlock,
+    // Portion of the management fee redirected to referrers (`referral_fee_percentage`)
+    Referral,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+pub struct FeeChangeProposal {
+    pub new_value: u16,
+    /// time after which the change can be applied
+    pub effective_time: Timestamp,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+pub struct FeeChangeLimitsProposal {
+    pub new_delay: u64,
+    pub new_period: u64,
+    pub new_max_change: u16,
+    /// time after which the limits can be applied
+    pub effective_time: Timestamp,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+pub enum KeeperReward {
+    // Fixed amount of AZERO per call
+    Fixed(Balance),
+    // Portion of the AZERO processed by the call expressed in basis points
+    Percentage(u16),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+pub struct BufferUnbonding {
+    pub azero: Balance,
+    /// time after which the AZERO can be withdrawn from the agents
+    pub claimable_at: Timestamp,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum UnlockRequestStatus {
+    // Batch unlock request has not been sent
+    Pending,
+    // Batch unlock request has been sent and is within the cooldown period
+    Cooldown,
+    // AZERO can be redeemed
+    Claimable,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct UnlockRequestDetails {
+    pub unlock_id: u128,
+    pub share_amount: Balance,
+    pub batch_id: u64,
+    pub status: UnlockRequestStatus,
+    /// spot value when pending, otherwise the value fixed when the batch was sent
+    pub azero: Balance,
+    /// time at which AZERO can be redeemed, unknown until the batch is sent
+    pub claimable_at: Option<Timestamp>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct UserUnlockSummary {
+    pub pending_shares: Balance,
+    pub cooldown_azero: Balance,
+    pub claimable_azero: Balance,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+pub struct FeeRecipient {
+    pub account: AccountId,
+    /// portion of withdrawn fees expressed in basis points
+    pub weight: u16,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+pub struct ReferrerInfo {
+    /// accumulated AZERO staked via the referral id
+    pub volume: Balance,
+    /// sAZERO currently attributed to the referrer
+    pub referred_shares: Balance,
+    /// rewards already accounted for by `referred_shares`
+    pub reward_debt: Balance,
+    /// settled rewards (sAZERO) which have not been claimed
+    pub unclaimed: Balance,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+pub struct ReferredStake {
+    pub referrer: AccountId,
+    pub shares: Balance,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct FeeBreakdown {
+    /// annualized management fee expressed in basis points
+    pub fee_percentage: u16,
+    /// portion of compounded rewards expressed in basis points
+    pub performance_fee_percentage: u16,
+    /// claimable virtual shares from all fees
+    pub virtual_shares: Balance,
+    /// lifetime virtual shares accrued by the performance fee
+    pub performance_fee_shares: Balance,
+    /// lifetime AZERO value charged by the performance fee
+    pub performance_fee_azero: Balance,
+}
+
 #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
 #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
 pub enum VaultError {
@@ -58,6 +366,30 @@ pub enum VaultError {
     ZeroTotalWeight,
     ZeroCompounding,
     MinimumStake,
+    MaximumTotalPooled,
+    MaximumAccountStake,
+    MinimumAzero,
+    MinimumShares,
+    DeadlineExpired,
+    InsufficientLiquidity,
+    InstantUnlockLimit,
+    InvalidFeeSplit,
+    InvalidReferral,
+    NotAllowlisted,
+    Paused,
+    FeeCeiling,
+    FeeChangeLimit,
+    FeeChangePending,
+    FeeChangeNotReady,
+    NoFeeChangeQueued,
+    CodeDelay,
+    CodeChangeNotReady,
+    NoCodeProposed,
+    InvalidVersion,
+    MigrationPending,
+    InsufficientInsurance,
+    ZeroLoss,
+    ZeroShares,
     /// An interaction with ink! environment has failed
     // NOTE: We're representing the `ink::env::Error` as `String` b/c the
     // type does not have Encode/Decode implemented.
@@ -70,14 +402,18 @@ pub enum VaultError {
 #[ink::storage_item]
 #[derive(Debug)]
 pub struct VaultData {
-    /// account that can withdraw fees, set minimum stake, and upgrade the Vault
-    pub role_owner: AccountId,
-    /// account that can adjust fees
-    pub role_adjust_fee: AccountId,
-    /// account that can change the `role_adjust_fee` account
-    pub role_adjust_fee_admin: AccountId,
+    /// account and admin of each role
+    pub roles: Mapping<RoleType, Role>,
+    /// role transfers awaiting acceptance by the proposed account or admin
+    pub pending_roles: Mapping<RoleType, PendingRole>,
     /// contract creation block timestamp
     pub creation_time: Timestamp,
+    /// storage layout version of `VaultData`, see `MIGRATIONS`
+    pub version: u32,
+    /// code hash awaiting its timelock before it can be applied
+    pub code_proposal: Option<CodeProposal>,
+    /// operations which are currently paused
+    pub paused: PauseState,
 
     /// total AZERO staked excluding AZERO being unbonded
     pub total_pooled: Balance,
@@ -87,16 +423,36 @@ pub struct VaultData {
     pub total_shares_virtual: Balance,
     /// minimum amount of AZERO a user needs to stake
     pub minimum_stake: Balance,
+    /// maximum total AZERO backing sAZERO after a stake
+    pub max_total_pooled: Balance,
+    /// only allowlisted accounts can stake when enabled
+    pub allowlist_enabled: bool,
+    /// accounts allowed to stake while the allowlist is enabled
+    pub allowlist: Mapping<AccountId, ()>,
+    /// Merkle root of additional accounts allowed to stake via `stake_with_proof`
+    pub allowlist_merkle_root: Option<[u8; 32]>,
+    /// maximum net AZERO staked by an account, unlimited when None
+    pub max_account_stake: Option<Balance>,
+    /// net AZERO staked by each account, reduced by the value of unlocked sAZERO
+    pub account_net_staked: Mapping<AccountId, Balance>,
 
     /// record of all batched unlock requests indexed by batch id
     pub batch_unlock_requests: Mapping<u64, UnlockRequestBatch>,
-    /// record of each user's unlock requests indexed by user AccountId
-    pub user_unlock_requests: Mapping<AccountId, Vec<UnlockRequest>>,
+    /// record of all unlock requests indexed by unlock id
+    pub unlock_requests: Mapping<u128, UnlockRequest>,
+    /// ids of each user's unlock requests indexed by user AccountId
+    pub user_unlock_requests: Mapping<AccountId, Vec<u128>>,
+    /// id assigned to the next unlock request
+    pub next_unlock_id: u128,
 
     /// time required after submitting a batch unlock request until AZERO is withdraw-able
     pub cooldown_period: u64,
     /// minimum time between creating batch unlock requests to prevent excess request errors
     pub batch_interval_delay: u64,
+    /// oldest batch id which may not have been sent, all prior batches are sent or empty
+    pub oldest_unsent_batch_id: u64,
+    /// maximum batch unlock requests sent during a user interaction, zero disables lazy sending
+    pub max_batches_per_dispatch: u32,
 
     /// last update time of claimable fees variable only modified by stake, redeem, withdraw_fees, and adjust_fee
     pub last_fee_update: Timestamp,
@@ -104,6 +460,94 @@ pub struct VaultData {
     pub fee_percentage: u16,
     /// compounding incentive percentage expressed in basis points
     pub incentive_percentage: u16,
+    /// recipients of withdrawn fees, all fees are withdrawn to the owner when empty
+    pub fee_recipients: Vec<FeeRecipient>,
+
+    /// portion of withdrawn fee shares retained by the insurance fund expressed in basis points
+    pub insurance_fee_percentage: u16,
+    /// portion of compounding incentives retained by the insurance fund expressed in basis points
+    pub insurance_incentive_percentage: u16,
+    /// sAZERO held by the Vault for the insurance fund
+    pub insurance_shares: Balance,
+    /// AZERO held by the Vault for the insurance fund
+    pub insurance_azero: Balance,
+
+    /// time over which donated AZERO is released into `total_pooled`, donations are released immediately when 0
+    pub donation_duration: u64,
+    /// donated AZERO which is bonded but not yet released into `total_pooled`
+    pub donation_stream: ReleaseStream,
+    /// time over which compounded AZERO vests into `total_pooled`, rewards vest immediately when 0
+    pub reward_vesting_period: u64,
+    /// compounded AZERO which is bonded but not yet vested into `total_pooled`
+    pub reward_stream: ReleaseStream,
+
+    /// portion of the management fee on referred sAZERO redirected to referrers expressed in basis points
+    pub referral_fee_percentage: u16,
+    /// only registered referral ids are valid when enabled
+    pub referral_whitelist_enabled: bool,
+    /// registered referral ids
+    pub referral_whitelist: Mapping<AccountId, ()>,
+    /// referral accounting indexed by referral id
+    pub referrers: Mapping<AccountId, ReferrerInfo>,
+    /// sAZERO attributed to a referrer indexed by staker AccountId
+    /// only updated when staking with a referral, unlocking, or cancelling an unlock, plain sAZERO transfers are not tracked
+    pub referred_stakes: Mapping<AccountId, ReferredStake>,
+    /// referred sAZERO removed from attribution by an unlock request indexed by unlock id
+    pub unlock_referrals: Mapping<u128, ReferredStake>,
+    /// total sAZERO attributed to referrers
+    pub total_referred_shares: Balance,
+    /// accumulated referral rewards per referred share scaled by `REFERRAL_PRECISION`
+    pub referral_reward_per_share: u128,
+    /// virtual shares reserved for referral rewards which cannot be withdrawn as fees
+    pub referral_shares_reserved: Balance,
+    /// performance fee percentage of compounded rewards expressed in basis points
+    pub performance_fee_percentage: u16,
+    /// lifetime virtual shares accrued by the performance fee
+    pub total_performance_fee_shares: Balance,
+    /// lifetime AZERO value charged by the performance fee
+    pub total_performance_fee_azero: Balance,
+    /// queued fee changes indexed by fee kind
+    pub fee_change_proposals: Mapping<FeeKind, FeeChangeProposal>,
+    /// time the last queued change of each fee kind was applied
+    pub last_fee_change: Mapping<FeeKind, Timestamp>,
+    /// time between queueing and applying a fee change
+    pub fee_change_delay: u64,
+    /// minimum time between applied changes of the same fee kind
+    pub fee_change_period: u64,
+    /// maximum change of a fee per change expressed in basis points
+    pub max_fee_change: u16,
+    /// queued change of `fee_change_delay`, `fee_change_period`, and `max_fee_change`
+    pub fee_change_limits_proposal: Option<FeeChangeLimitsProposal>,
+    /// reward paid from accrued fees for sending batch unlock requests and withdrawing unbonded AZERO
+    pub keeper_reward: KeeperReward,
+
+    /// AZERO held by the vault which can be paid out immediately via instant unlocks
+    pub liquidity_buffer: Balance,
+    /// AZERO being unbonded to replenish the liquidity buffer
+    pub buffer_unbonding: Balance,
+    /// unbonding chunks of `buffer_unbonding` in ascending order of `claimable_at`
+    pub buffer_unbonding_chunks: Vec<BufferUnbonding>,
+    /// target size of the liquidity buffer relative to total AZERO expressed in basis points
+    pub buffer_target_percentage: u16,
+    /// instant unlock fee percentage expressed in basis points
+    pub instant_unlock_fee_percentage: u16,
+    /// maximum amount of AZERO which can be instantly unlocked per call
+    pub max_instant_unlock: Balance,
+
+    /// ring buffer of redemption ratio checkpoints indexed by checkpoint count modulo `MAX_RATE_CHECKPOINTS`
+    pub rate_checkpoints: Mapping<u32, RateCheckpoint>,
+    /// total checkpoints recorded
+    pub rate_checkpoint_count: u32,
+    /// minimum time between rate checkpoints
+    pub checkpoint_granularity: u64,
+    /// ring buffer of compound results indexed by record count modulo `MAX_COMPOUND_RECORDS`
+    pub compound_records: Mapping<u32, CompoundRecord>,
+    /// total compound results recorded
+    pub compound_record_count: u32,
+    /// AZERO expected to be bonded by each agent, used to detect slashes
+    pub agent_stakes: Mapping<AccountId, Balance>,
+    /// AZERO lost by each agent's nomination pool as reported by the owner, not reflected in the agent's staked value
+    pub agent_losses: Mapping<AccountId, Balance>,
 
     /// token contract used for representing protocol staked AZERO ownership
     pub shares_contract: AccountId,
@@ -119,31 +563,182 @@ impl VaultData {
         current_time: Timestamp,
         era: u64,
     ) -> VaultData {
+        let mut initial_roles = Mapping::default();
+        for role_type in ROLE_TYPES {
+            initial_roles.insert(role_type, &Role { admin, account: admin });
+        }
+
         VaultData {
-            role_owner: admin,
-            role_adjust_fee: admin,
-            role_adjust_fee_admin: admin,
+            roles: initial_roles,
+            pending_roles: Mapping::default(),
             creation_time: current_time,
+            version: VAULT_VERSION,
+            code_proposal: None,
+            paused: PauseState::default(),
             total_pooled: 0,
             total_shares_minted: 0,
             total_shares_virtual: 0,
             minimum_stake: 0,
+            max_total_pooled: Balance::MAX, // unlimited
+            allowlist_enabled: false,
+            allowlist: Mapping::default(),
+            allowlist_merkle_root: None,
+            max_account_stake: None,
+            account_net_staked: Mapping::default(),
             batch_unlock_requests: Mapping::default(),
+            unlock_requests: Mapping::default(),
             user_unlock_requests: Mapping::default(),
+            next_unlock_id: 0,
             cooldown_period: era * 14,
             batch_interval_delay: era * 2,
+            oldest_unsent_batch_id: 0,
+            max_batches_per_dispatch: 0, // disabled
             last_fee_update: current_time,
             fee_percentage: 2_00, // 2.00%
             incentive_percentage: 0_05, // 0.05%
+            fee_recipients: Vec::new(),
+            insurance_fee_percentage: 0,
+            insurance_incentive_percentage: 0,
+            insurance_shares: 0,
+            insurance_azero: 0,
+            donation_duration: 0,
+            donation_stream: ReleaseStream::default(),
+            reward_vesting_period: 0,
+            reward_stream: ReleaseStream::default(),
+            referral_fee_percentage: 0, // disabled
+            referral_whitelist_enabled: false,
+            referral_whitelist: Mapping::default(),
+            referrers: Mapping::default(),
+            referred_stakes: Mapping::default(),
+            unlock_referrals: Mapping::default(),
+            total_referred_shares: 0,
+            referral_reward_per_share: 0,
+            referral_shares_reserved: 0,
+            performance_fee_percentage: 0, // disabled
+            total_performance_fee_shares: 0,
+            total_performance_fee_azero: 0,
+            fee_change_proposals: Mapping::default(),
+            last_fee_change: Mapping::default(),
+            fee_change_delay: 0,
+            fee_change_period: 0,
+            max_fee_change: BIPS, // unlimited
+            fee_change_limits_proposal: None,
+            keeper_reward: KeeperReward::Fixed(0), // disabled
+            liquidity_buffer: 0,
+            buffer_unbonding: 0,
+            buffer_unbonding_chunks: Vec::new(),
+            buffer_target_percentage: 0, // disabled
+            instant_unlock_fee_percentage: 30, // 0.30%
+            max_instant_unlock: 0, // disabled
+            rate_checkpoints: Mapping::default(),
+            rate_checkpoint_count: 0,
+            checkpoint_granularity: DAY / 24, // 1 hour
+            compound_records: Mapping::default(),
+            compound_record_count: 0,
+            agent_stakes: Mapping::default(),
+            agent_losses: Mapping::default(),
             shares_contract: shares_contract_,
             registry_contract: registry_ref,
         }
     }
 
+    /// Returns the account holding a role
+    pub fn get_role(&self, role_type: RoleType) -> AccountId {
+        self.roles.get(role_type).unwrap().account
+    }
+
+    /// Returns the account which can transfer a role
+    pub fn get_role_admin(&self, role_type: RoleType) -> AccountId {
+        self.roles.get(role_type).unwrap().admin
+    }
+
+    /// Stores the pending transfers of a role, removing the entry when nothing is pending
+    pub fn set_pending_role(&mut self, role_type: RoleType, pending: &PendingRole) {
+        if *pending == PendingRole::default() {
+            self.pending_roles.remove(role_type);
+        } else {
+            self.pending_roles.insert(role_type, pending);
+        }
+    }
+
     pub fn get_batch_unlock_id(&self, time: Timestamp) -> u64 {
         (time - self.creation_time) / self.batch_interval_delay
     }
 
+    /// Stores a new unlock request and indexes it under its owner
+    ///
+    /// Returns the unlock id
+    pub fn add_unlock_request(&mut self, unlock_request: &UnlockRequest) -> u128 {
+        let unlock_id = self.next_unlock_id;
+        self.next_unlock_id += 1;
+
+        self.unlock_requests.insert(unlock_id, unlock_request);
+        self.index_unlock_request(unlock_request.owner, unlock_id);
+
+        unlock_id
+    }
+
+    /// Fetches an unlock request ensuring it belongs to `user`
+    pub fn get_user_unlock_request(&self, user: AccountId, unlock_id: u128) -> Result<UnlockRequest, VaultError> {
+        match self.unlock_requests.get(unlock_id) {
+            Some(unlock_request) if unlock_request.owner == user => Ok(unlock_request),
+            _ => Err(VaultError::InvalidUserUnlockRequest),
+        }
+    }
+
+    /// Deletes an unlock request and removes it from its owner's index
+    pub fn remove_unlock_request(&mut self, unlock_id: u128) {
+        if let Some(unlock_request) = self.unlock_requests.get(unlock_id) {
+            self.unlock_requests.remove(unlock_id);
+            self.unlock_referrals.remove(unlock_id);
+            self.unindex_unlock_request(unlock_request.owner, unlock_id);
+        }
+    }
+
+    /// Calculates the AZERO redeemable for an unlock request
+    ///
+    /// Associated batch unlock request must have been sent
+    /// Associated batch unlock request must have completed its cooldown period
+    pub fn get_redeemable_azero(&self, unlock_request: &UnlockRequest, now: Timestamp) -> Result<Balance, VaultError> {
+        // Ensure batch unlock has been redeemed
+        let batch_unlock_request = self.batch_unlock_requests.get(unlock_request.batch_id).unwrap();
+        if batch_unlock_request.redemption_timestamp.is_none() || batch_unlock_request.value_at_redemption.is_none() {
+            return Err(VaultError::InvalidBatchUnlockRequest);
+        }
+
+        // Ensure batch unlock has completed
+        let time_since_redemption = now - batch_unlock_request.redemption_timestamp.unwrap();
+        if time_since_redemption < self.cooldown_period {
+            return Err(VaultError::CooldownPeriod);
+        }
+
+        Ok(self.pro_rata(
+            unlock_request.share_amount,
+            batch_unlock_request.value_at_redemption.unwrap(),
+            batch_unlock_request.total_shares,
+        ))
+    }
+
+    /// Adds an unlock id to a user's index
+    pub fn index_unlock_request(&mut self, user: AccountId, unlock_id: u128) {
+        let mut user_unlock_ids = self.user_unlock_requests.get(user).unwrap_or_default();
+        user_unlock_ids.push(unlock_id);
+        self.user_unlock_requests.insert(user, &user_unlock_ids);
+    }
+
+    /// Removes an unlock id from a user's index preserving the order of the remaining ids
+    pub fn unindex_unlock_request(&mut self, user: AccountId, unlock_id: u128) {
+        let mut user_unlock_ids = self.user_unlock_requests.get(user).unwrap_or_default();
+        if let Some(index) = user_unlock_ids.iter().position(|&id| id == unlock_id) {
+            user_unlock_ids.remove(index);
+        }
+        if user_unlock_ids.is_empty() {
+            self.user_unlock_requests.remove(user);
+        } else {
+            self.user_unlock_requests.insert(user, &user_unlock_ids);
+        }
+    }
+
     /// Calculates differences between current staked amounts and optimal staked amounts
     ///
     /// # Returns
@@ -166,7 +761,7 @@ impl VaultData {
         let mut imbalances = Vec::new();
 
         for a in agents.into_iter() {
-            let staked_amount_current = query_staked_value(a.address) as i128;
+            let staked_amount_current = self.get_pool_balance(a.address) as i128;
             let staked_amount_optimal = if total_weight > 0 {
                 self.pro_rata(a.weight as u128, total_pooled, total_weight as u128) as i128
             } else {
@@ -262,6 +857,7 @@ impl VaultData {
                 if let Err(e) = call_deposit(a.address, deposit_amount) {
                     return Err(VaultError::InternalError(e));
                 }
+                self.add_agent_stake(a.address, deposit_amount);
             }
         }
 
@@ -355,6 +951,7 @@ impl VaultData {
                 if let Err(e) = call_unbond(a.address, unbond_amount) {
                     return Err(VaultError::InternalError(e));
                 }
+                self.sub_agent_stake(a.address, unbond_amount);
             }
         }
 
@@ -377,25 +974,37 @@ impl VaultData {
     }
 
     /// Claim payouts and re-bond AZERO from the agents looping over each nominator pool
+    /// Records the compound result of each agent for yield estimation
+    /// Compounded AZERO vests into `total_pooled` over `reward_vesting_period`
     ///
     /// # Returns
     ///
     /// `total_compounded` - Total AZERO compounded across all agents
     /// `total_incentive` - Total AZERO incentive from all agents
-    pub fn delegate_compound(&mut self) -> Result<(Balance, Balance), VaultError> {
+    /// `total_loss` - Shortfall of the agents' pool balances relative to the bonded AZERO before compounding,
+    ///     0 when within `GUARDIAN_LOSS_TOLERANCE` of the bonded AZERO
+    pub fn delegate_compound(&mut self, current_time: Timestamp) -> Result<(Balance, Balance, Balance), VaultError> {
         let (_total_weight, agents) = self.registry_contract.get_agents();
 
         let mut total_compounded = 0;
         let mut total_incentive = 0;
+        let mut agent_compounds: Vec<AgentCompound> = Vec::new();
 
         let incentive_percentage_ = self.incentive_percentage; // shadow
 
         for (i, a) in agents.into_iter().enumerate() {
+            let staked = self.get_pool_balance(a.address);
             match call_compound(a.address, incentive_percentage_) {
                 Ok((compound_amount, incentive_amount)) => {
                     debug_println!("Compounded {} to agent #{}", compound_amount, i);
                     total_compounded += compound_amount;
                     total_incentive += incentive_amount;
+                    self.add_agent_stake(a.address, compound_amount);
+                    agent_compounds.push(AgentCompound {
+                        agent: a.address,
+                        compounded: compound_amount,
+                        staked,
+                    });
                 },
                 Err(e) => return Err(VaultError::InternalError(e)),
             }
@@ -405,32 +1014,672 @@ impl VaultData {
             return Err(VaultError::ZeroCompounding);
         }
 
+        // Pool balances fall short of the bonded AZERO, including unreleased donations and rewards, after a slash
+        let expected = self.total_pooled + self.donation_stream.remaining + self.reward_stream.remaining;
+        let total_staked: Balance = agent_compounds.iter().map(|a| a.staked).sum();
+        let total_loss = expected.saturating_sub(total_staked);
+        let total_loss = if self.exceeds_loss_tolerance(total_loss, expected) { total_loss } else { 0 };
+
+        self.add_compound_record(CompoundRecord {
+            timestamp: current_time,
+            compounded: total_compounded,
+            total_pooled: self.total_pooled,
+            agents: agent_compounds,
+        });
+
         self.total_pooled += total_compounded;
+        self.add_rewards(total_compounded, current_time);
+
+        Ok((total_compounded, total_incentive, total_loss))
+    }
+
+    /// Compares the pool balance of each agent against the expected amount
+    /// Losses are socialized by reducing `total_pooled`, lowering the redemption ratio for all sAZERO
+    /// including shares held by unsent unlock batches
+    ///
+    /// Pool balances above the expected amount, such as after a corrected loss report, are added back to `total_pooled`
+    /// Agents without an expected amount are initialized from their pool balance
+    ///
+    /// # Returns
+    ///
+    /// The total AZERO lost, the total AZERO recovered, and the agents which reported a loss
+    pub fn sync_pool_values(&mut self) -> (Balance, Balance, Vec<AgentLoss>) {
+        let (_total_weight, agents) = self.registry_contract.get_agents();
+
+        let mut total_loss = 0;
+        let mut total_recovered = 0;
+        let mut losses: Vec<AgentLoss> = Vec::new();
+
+        for a in agents.into_iter() {
+            let actual = self.get_pool_balance(a.address);
+            if let Some(expected) = self.agent_stakes.get(a.address) {
+                if actual < expected {
+                    total_loss += expected - actual;
+                    losses.push(AgentLoss {
+                        agent: a.address,
+                        expected,
+                        actual,
+                    });
+                } else {
+                    total_recovered += actual - expected;
+                }
+            }
+            self.agent_stakes.insert(a.address, &actual);
+        }
+
+        let total_loss = if total_loss > self.total_pooled { self.total_pooled } else { total_loss };
+        self.total_pooled = self.total_pooled - total_loss + total_recovered;
+
+        (total_loss, total_recovered, losses)
+    }
+
+    /// Returns the AZERO held by an agent's nomination pool
+    /// Excludes losses reported by the owner which are not reflected in the agent's staked value
+    pub fn get_pool_balance(&self, agent: AccountId) -> Balance {
+        query_staked_value(agent).saturating_sub(self.agent_losses.get(agent).unwrap_or(0))
+    }
+
+    /// Returns true when `loss` exceeds `GUARDIAN_LOSS_TOLERANCE` of the `expected` AZERO
+    pub fn exceeds_loss_tolerance(&self, loss: Balance, expected: Balance) -> bool {
+        loss > self.pro_rata(expected, GUARDIAN_LOSS_TOLERANCE as u128, BIPS as u128)
+    }
+
+    fn add_agent_stake(&mut self, agent: AccountId, azero: Balance) {
+        let stake = self.agent_stakes.get(agent).unwrap_or(0);
+        self.agent_stakes.insert(agent, &(stake + azero));
+    }
+
+    fn sub_agent_stake(&mut self, agent: AccountId, azero: Balance) {
+        let stake = self.agent_stakes.get(agent).unwrap_or(0);
+        self.agent_stakes.insert(agent, &stake.saturating_sub(azero));
+    }
+
+    /// Charges the performance fee on compounded AZERO by accruing virtual shares
+    /// Must be called after the compounded AZERO is recorded by `delegate_compound()`
+    ///
+    /// Returns the AZERO value of the fee and the virtual shares accrued
+    pub fn charge_performance_fee(&mut self, compounded: Balance, current_time: Timestamp) -> (Balance, Balance) {
+        if self.performance_fee_percentage == 0 {
+            return (0, 0);
+        }
+
+        // Management fees accrue against the supply before the performance fee
+        self.update_fees(current_time);
+
+        // Fee shares are valued against the total AZERO once all rewards have vested and donations are released
+        let total_shares = self.total_shares_minted + self.total_shares_virtual;
+        let total_value = self.get_total_value() + self.reward_stream.remaining + self.donation_stream.remaining;
+        let fee = self.pro_rata(compounded, self.performance_fee_percentage as u128, BIPS as u128);
+        if total_shares == 0 || fee == 0 || fee >= total_value {
+            return (0, 0);
+        }
+
+        // Shares worth `fee` after accrual: shares / (total_shares + shares) = fee / total_value
+        let shares = self.pro_rata(fee, total_shares, total_value - fee);
+
+        self.total_shares_virtual += shares;
+        self.total_performance_fee_shares += shares;
+        self.total_performance_fee_azero += fee;
+
+        (fee, shares)
+    }
+
+    /// Returns the total AZERO backing sAZERO
+    /// Includes bonded AZERO, the liquidity buffer, and AZERO being unbonded into the liquidity buffer
+    pub fn get_total_value(&self) -> Balance {
+        self.total_pooled + self.liquidity_buffer + self.buffer_unbonding
+    }
+
+    /// Returns the total AZERO backing sAZERO at `current_time`
+    /// Includes streamed AZERO released since the last `update_fees()` for read-only queries
+    pub fn get_total_value_at(&self, current_time: Timestamp) -> Balance {
+        self.get_total_value() + self.get_pending_release(current_time)
+    }
+
+    /// Calculates the portion of a deposit which is retained in the liquidity buffer
+    ///
+    /// Retains `buffer_target_percentage` of the deposit until the buffer reaches its target size
+    pub fn get_buffer_deposit(&self, azero: Balance) -> Balance {
+        let buffer_target = self.pro_rata(
+            self.get_total_value() + azero,
+            self.buffer_target_percentage as u128,
+            BIPS as u128,
+        );
+        let buffer_current = self.liquidity_buffer + self.buffer_unbonding;
+        if buffer_target <= buffer_current {
+            return 0;
+        }
+
+        let deposit = self.pro_rata(azero, self.buffer_target_percentage as u128, BIPS as u128);
+        if deposit < buffer_target - buffer_current { deposit } else { buffer_target - buffer_current }
+    }
+
+    /// Records AZERO unbonded to replenish the liquidity buffer
+    pub fn add_buffer_unbonding(&mut self, azero: Balance, current_time: Timestamp) {
+        self.buffer_unbonding += azero;
+        self.buffer_unbonding_chunks.push(BufferUnbonding {
+            azero,
+            claimable_at: current_time + self.cooldown_period,
+        });
+    }
+
+    /// Moves withdrawn buffer AZERO into the liquidity buffer
+    /// Only chunks which are claimable are moved so AZERO owed to redeemers is never credited to the buffer
+    ///
+    /// `withdrawn` - AZERO withdrawn from the agents
+    ///
+    /// Returns the AZERO moved into the liquidity buffer
+    pub fn replenish_buffer(&mut self, withdrawn: Balance, current_time: Timestamp) -> Balance {
+        let mut replenished = 0;
+        while let Some(chunk) = self.buffer_unbonding_chunks.first_mut() {
+            if chunk.claimable_at > current_time || replenished == withdrawn {
+                break;
+            }
+
+            let remaining = withdrawn - replenished;
+            if chunk.azero > remaining {
+                chunk.azero -= remaining;
+                replenished = withdrawn;
+            } else {
+                replenished += chunk.azero;
+                self.buffer_unbonding_chunks.remove(0);
+            }
+        }
+
+        self.buffer_unbonding -= replenished;
+        self.liquidity_buffer += replenished;
+
+        replenished
+    }
+
+    /// Calculates the bonded AZERO which should be unbonded to restore the liquidity buffer to its target size
+    ///
+    /// `unbonding` - AZERO which is already being unbonded for unlock requests
+    pub fn get_buffer_refill(&self, unbonding: Balance) -> Balance {
+        let buffer_target = self.pro_rata(
+            self.get_total_value() - unbonding,
+            self.buffer_target_percentage as u128,
+            BIPS as u128,
+        );
+        let buffer_current = self.liquidity_buffer + self.buffer_unbonding;
+        if buffer_target <= buffer_current {
+            return 0;
+        }
+
+        let available = self.total_pooled.saturating_sub(unbonding);
+        if buffer_target - buffer_current < available { buffer_target - buffer_current } else { available }
+    }
+
+    /// Returns the remaining AZERO which can be staked before reaching `max_total_pooled`
+    /// Measured against the total AZERO backing sAZERO, including the liquidity buffer, unreleased donations and unvested rewards
+    pub fn get_remaining_capacity(&self) -> Balance {
+        let total = self.get_total_value() + self.donation_stream.remaining + self.reward_stream.remaining;
+        self.max_total_pooled.saturating_sub(total)
+    }
+
+    /// Returns the remaining AZERO which can be staked by `account`
+    /// Limited by both `max_total_pooled` and `max_account_stake`
+    pub fn get_account_remaining_capacity(&self, account: AccountId) -> Balance {
+        let remaining = self.get_remaining_capacity();
+        match self.max_account_stake {
+            Some(max_account_stake) => {
+                let net_staked = self.account_net_staked.get(account).unwrap_or(0);
+                let account_remaining = max_account_stake.saturating_sub(net_staked);
+                if account_remaining < remaining { account_remaining } else { remaining }
+            },
+            None => remaining,
+        }
+    }
+
+    /// Increases the net AZERO staked by `account`
+    pub fn add_net_staked(&mut self, account: AccountId, azero: Balance) {
+        let net_staked = self.account_net_staked.get(account).unwrap_or(0);
+        self.account_net_staked.insert(account, &(net_staked + azero));
+    }
+
+    /// Decreases the net AZERO staked by `account`, saturating at zero
+    pub fn sub_net_staked(&mut self, account: AccountId, azero: Balance) {
+        let net_staked = self.account_net_staked.get(account).unwrap_or(0).saturating_sub(azero);
+        if net_staked == 0 {
+            self.account_net_staked.remove(account);
+        } else {
+            self.account_net_staked.insert(account, &net_staked);
+        }
+    }
+
+    /// Returns the current value of a fee
+    pub fn get_fee_value(&self, kind: FeeKind) -> u16 {
+        match kind {
+            FeeKind::Management => self.fee_percentage,
+            FeeKind::Incentive => self.incentive_percentage,
+            FeeKind::Performance => self.performance_fee_percentage,
+            FeeKind::InstantUnlock => self.instant_unlock_fee_percentage,
+            FeeKind::Referral => self.referral_fee_percentage,
+        }
+    }
+
+    /// Returns the hard ceiling of a fee
+    pub fn get_fee_ceiling(&self, kind: FeeKind) -> u16 {
+        match kind {
+            FeeKind::Management => MAX_FEE,
+            FeeKind::Incentive => MAX_INCENTIVE,
+            FeeKind::Performance => MAX_PERFORMANCE_FEE,
+            FeeKind::InstantUnlock | FeeKind::Referral => BIPS - 1,
+        }
+    }
+
+    /// Splits `shares` among the fee recipients according to their weights
+    /// Rounding dust is allocated to the last recipient
+    pub fn split_fees(&self, shares: Balance) -> Vec<(AccountId, Balance)> {
+        if self.fee_recipients.is_empty() {
+            return [(self.get_role(RoleType::Owner), shares)].to_vec();
+        }
+
+        let mut remaining = shares;
+        let last = self.fee_recipients.len() - 1;
+        self.fee_recipients
+            .iter()
+            .enumerate()
+            .map(|(i, recipient)| {
+                let portion = if i == last {
+                    remaining
+                } else {
+                    self.pro_rata(shares, recipient.weight as u128, BIPS as u128)
+                };
+                remaining -= portion;
+                (recipient.account, portion)
+            })
+            .collect()
+    }
+
+    /// Calculates the AZERO reward for a keeper call which processed `processed` AZERO
+    ///
+    /// Reward never exceeds the processed value
+    pub fn get_keeper_reward(&self, processed: Balance) -> Balance {
+        let reward = match self.keeper_reward {
+            KeeperReward::Fixed(azero) => azero,
+            KeeperReward::Percentage(bips) => self.pro_rata(processed, bips as u128, BIPS as u128),
+        };
+        if reward < processed { reward } else { processed }
+    }
+
+    /// Calculates the rate of a checkpoint with RATE_PRECISION
+    pub fn get_checkpoint_rate(&self, checkpoint: &RateCheckpoint) -> u128 {
+        if checkpoint.total_shares == 0 {
+            0
+        } else {
+            self.pro_rata(RATE_PRECISION, checkpoint.total_pooled, checkpoint.total_shares)
+        }
+    }
+
+    /// Records a rate checkpoint, overwriting the oldest once `MAX_RATE_CHECKPOINTS` are retained
+    /// Skipped when the latest checkpoint is within `checkpoint_granularity`
+    pub fn add_rate_checkpoint(&mut self, checkpoint: RateCheckpoint) {
+        let count = self.rate_checkpoint_count;
+        if count > 0 {
+            let latest = self.rate_checkpoints.get((count - 1) % MAX_RATE_CHECKPOINTS).unwrap();
+            if checkpoint.timestamp <= latest.timestamp
+                || checkpoint.timestamp < latest.timestamp + self.checkpoint_granularity
+            {
+                return;
+            }
+        }
+
+        self.rate_checkpoints.insert(count % MAX_RATE_CHECKPOINTS, &checkpoint);
+        self.rate_checkpoint_count = count + 1;
+    }
+
+    /// Returns the retained rate checkpoints from newest to oldest
+    fn get_rate_checkpoints_desc(&self) -> impl Iterator<Item = RateCheckpoint> + '_ {
+        let count = self.rate_checkpoint_count;
+        let retained = if count < MAX_RATE_CHECKPOINTS { count } else { MAX_RATE_CHECKPOINTS };
+        (1..=retained).map(move |i| self.rate_checkpoints.get((count - i) % MAX_RATE_CHECKPOINTS).unwrap())
+    }
+
+    /// Returns the rate of the latest checkpoint at or before `timestamp`
+    pub fn get_rate_at(&self, timestamp: Timestamp) -> Option<u128> {
+        self.get_rate_checkpoints_desc()
+            .find(|checkpoint| checkpoint.timestamp <= timestamp)
+            .map(|checkpoint| self.get_checkpoint_rate(&checkpoint))
+    }
+
+    /// Calculates the time-weighted average rate over `window` ending at `current_time`
+    /// Each checkpoint rate holds until the next checkpoint
+    pub fn get_twap(&self, current_time: Timestamp, window: u64) -> Option<u128> {
+        if window == 0 || window > current_time {
+            return None;
+        }
+
+        let start = current_time - window;
+        let mut end = current_time;
+        let mut weighted_rate: u128 = 0;
+
+        for checkpoint in self.get_rate_checkpoints_desc() {
+            let segment_start = if checkpoint.timestamp > start { checkpoint.timestamp } else { start };
+            weighted_rate += self.get_checkpoint_rate(&checkpoint) * (end - segment_start) as u128;
+            end = segment_start;
+
+            if checkpoint.timestamp <= start {
+                return Some(weighted_rate / window as u128);
+            }
+        }
+
+        // Retained checkpoints do not cover the window
+        None
+    }
+
+    /// Records a compound result, overwriting the oldest once `MAX_COMPOUND_RECORDS` are retained
+    fn add_compound_record(&mut self, record: CompoundRecord) {
+        let count = self.compound_record_count;
+        self.compound_records.insert(count % MAX_COMPOUND_RECORDS, &record);
+        self.compound_record_count = count + 1;
+    }
+
+    /// Estimates annualized yields from the compound results within `window` ending at `current_time`
+    pub fn get_estimated_apy(&self, current_time: Timestamp, window: u64) -> ApyEstimate {
+        let mut estimate = ApyEstimate {
+            gross_apy: 0,
+            net_apy: 0,
+            agents: Vec::new(),
+        };
+        if window == 0 {
+            return estimate;
+        }
+
+        let start = current_time.saturating_sub(window);
+        let count = self.compound_record_count;
+        let retained = if count < MAX_COMPOUND_RECORDS { count } else { MAX_COMPOUND_RECORDS };
+
+        // Sum of yields of each compound result with RATE_PRECISION
+        let mut gross_yield: u128 = 0;
+        let mut agent_yields: Vec<(AccountId, u128)> = Vec::new();
+
+        for i in 1..=retained {
+            let record = self.compound_records.get((count - i) % MAX_COMPOUND_RECORDS).unwrap();
+            if record.timestamp < start {
+                break;
+            }
 
-        Ok((total_compounded, total_incentive))
+            if record.total_pooled > 0 {
+                gross_yield += self.pro_rata(record.compounded, RATE_PRECISION, record.total_pooled);
+            }
+            for a in record.agents.iter().filter(|a| a.staked > 0) {
+                let agent_yield = self.pro_rata(a.compounded, RATE_PRECISION, a.staked);
+                match agent_yields.iter_mut().find(|(agent, _)| *agent == a.agent) {
+                    Some((_, total)) => *total += agent_yield,
+                    None => agent_yields.push((a.agent, agent_yield)),
+                }
+            }
+        }
+
+        let annualize = |y: u128| -> u32 {
+            let apy = self.pro_rata(y, BIPS as u128 * YEAR as u128, RATE_PRECISION * window as u128);
+            if apy > u32::MAX as u128 { u32::MAX } else { apy as u32 }
+        };
+
+        estimate.gross_apy = annualize(gross_yield);
+        let after_performance_fee = self.pro_rata(
+            estimate.gross_apy as u128,
+            (BIPS - self.performance_fee_percentage) as u128,
+            BIPS as u128,
+        ) as u32;
+        estimate.net_apy = after_performance_fee.saturating_sub(self.fee_percentage as u32);
+        estimate.agents = agent_yields
+            .into_iter()
+            .map(|(agent, y)| AgentApy {
+                agent,
+                apy: annualize(y),
+            })
+            .collect();
+
+        estimate
+    }
+
+    /// Calculates the AZERO of a stream released between its last update and `current_time`
+    pub fn get_stream_release(&self, stream: &ReleaseStream, current_time: Timestamp) -> Balance {
+        if current_time >= stream.end {
+            stream.remaining
+        } else if current_time <= stream.last_update {
+            0
+        } else {
+            self.pro_rata(
+                stream.remaining,
+                (current_time - stream.last_update) as u128,
+                (stream.end - stream.last_update) as u128,
+            )
+        }
+    }
+
+    /// Calculates the AZERO of all streams released between their last update and `current_time`
+    pub fn get_pending_release(&self, current_time: Timestamp) -> Balance {
+        self.get_stream_release(&self.donation_stream, current_time)
+            + self.get_stream_release(&self.reward_stream, current_time)
+    }
+
+    /// Calculates the end of a stream after adding `azero` to be released over `duration`
+    /// Weights the remaining time of unreleased AZERO against `duration` so small additions barely delay it
+    /// Must be called after `release_streams()`
+    pub fn get_weighted_end(
+        &self,
+        stream: &ReleaseStream,
+        azero: Balance,
+        duration: u64,
+        current_time: Timestamp,
+    ) -> Timestamp {
+        let total = stream.remaining + azero;
+        if total == 0 {
+            return current_time + duration;
+        }
+
+        let remaining_time = stream.end.saturating_sub(current_time);
+        let weighted_time = self.pro_rata(stream.remaining, remaining_time as u128, total)
+            + self.pro_rata(azero, duration as u128, total);
+
+        current_time + weighted_time as u64
+    }
+
+    /// Releases streamed donations and vested rewards into `total_pooled`
+    pub fn release_streams(&mut self, current_time: Timestamp) {
+        let donations = self.get_stream_release(&self.donation_stream, current_time);
+        self.donation_stream.remaining -= donations;
+        self.donation_stream.last_update = current_time;
+
+        let rewards = self.get_stream_release(&self.reward_stream, current_time);
+        self.reward_stream.remaining -= rewards;
+        self.reward_stream.last_update = current_time;
+
+        self.total_pooled += donations + rewards;
+    }
+
+    /// Withholds bonded AZERO from `total_pooled` to be released linearly over `donation_duration`
+    /// Must be called after the donated AZERO is bonded via `delegate_bonding()`
+    ///
+    /// Extends the stream of any unreleased donations by the weighted duration, see `get_weighted_end()`
+    pub fn add_donation(&mut self, azero: Balance, current_time: Timestamp) {
+        if self.donation_duration == 0 {
+            return;
+        }
+
+        self.release_streams(current_time);
+
+        self.donation_stream.end = self.get_weighted_end(&self.donation_stream, azero, self.donation_duration, current_time);
+        self.total_pooled -= azero;
+        self.donation_stream.remaining += azero;
+    }
+
+    /// Withholds compounded AZERO from `total_pooled` to vest linearly over `reward_vesting_period`
+    /// Must be called after the compounded AZERO is added to `total_pooled`
+    ///
+    /// Extends the vesting of any unvested rewards by the weighted period, see `get_weighted_end()`
+    fn add_rewards(&mut self, azero: Balance, current_time: Timestamp) {
+        if self.reward_vesting_period == 0 {
+            return;
+        }
+
+        self.release_streams(current_time);
+
+        self.reward_stream.end = self.get_weighted_end(&self.reward_stream, azero, self.reward_vesting_period, current_time);
+        self.total_pooled -= azero;
+        self.reward_stream.remaining += azero;
     }
 
     /// Calculates summation of fees from last update until now
+    /// Releases streamed donations and vested rewards into `total_pooled`
     /// Must be called before changing: `total_shares_minted`, `fee_percentage`
     /// Must be called before calculating redemption ratio via: `get_shares_from_azero()` and `get_azero_from_shares()`
     pub fn update_fees(&mut self, current_time: Timestamp) {
+        self.release_streams(current_time);
+
         // Time since last update
         let time = current_time - self.last_fee_update;
 
         // Calculate fee accumulation since last update
         if time > 0 {
+            let total_shares = self.total_shares_minted + self.total_shares_virtual;
             let virtual_shares = self.pro_rata(
-                self.total_shares_minted + self.total_shares_virtual,
+                total_shares,
                 self.fee_percentage as u128,
                 BIPS as u128,
             );
             let time_weighted_virtual_shares = self.pro_rata(virtual_shares, time as u128, YEAR as u128);
 
+            // Redirect the referral portion of fees charged on referred shares
+            let reward_per_share = self.get_referral_reward_per_share(time_weighted_virtual_shares, total_shares);
+            self.referral_reward_per_share += reward_per_share;
+            self.referral_shares_reserved += self.pro_rata(
+                self.total_referred_shares,
+                reward_per_share,
+                REFERRAL_PRECISION,
+            );
+
             self.total_shares_virtual += time_weighted_virtual_shares;
             self.last_fee_update = current_time;
         }
     }
 
+    /// Calculates the referral reward per referred share from newly accrued fees
+    fn get_referral_reward_per_share(&self, fee_shares: Balance, total_shares: Balance) -> u128 {
+        if self.referral_fee_percentage == 0 || self.total_referred_shares == 0 || total_shares == 0 {
+            return 0;
+        }
+
+        let redirected = self.pro_rata(fee_shares, self.referral_fee_percentage as u128, BIPS as u128);
+        self.pro_rata(redirected, REFERRAL_PRECISION, total_shares)
+    }
+
+    /// Returns the accumulated referral rewards per referred share at the given time
+    pub fn get_referral_reward_per_share_at_time(&self, current_time: Timestamp) -> u128 {
+        let time = current_time - self.last_fee_update;
+
+        if time > 0 {
+            let total_shares = self.total_shares_minted + self.total_shares_virtual;
+            let virtual_shares = self.pro_rata(total_shares, self.fee_percentage as u128, BIPS as u128);
+            let time_weighted_virtual_shares = self.pro_rata(virtual_shares, time as u128, YEAR as u128);
+            self.referral_reward_per_share + self.get_referral_reward_per_share(time_weighted_virtual_shares, total_shares)
+        } else {
+            self.referral_reward_per_share
+        }
+    }
+
+    /// Returns the unclaimed referral rewards (sAZERO) of a referrer given the accumulated rewards per share
+    pub fn get_referral_rewards(&self, referrer: AccountId, reward_per_share: u128) -> Balance {
+        let info = self.referrers.get(referrer).unwrap_or_default();
+        let accumulated = self.pro_rata(info.referred_shares, reward_per_share, REFERRAL_PRECISION);
+        info.unclaimed + accumulated.saturating_sub(info.reward_debt)
+    }
+
+    /// Settles a referrer's rewards before changing the sAZERO attributed to it
+    /// Must be called after `update_fees()`
+    fn adjust_referrer(&mut self, referrer: AccountId, added: Balance, removed: Balance, volume: Balance) {
+        let mut info = self.referrers.get(referrer).unwrap_or_default();
+
+        info.unclaimed = self.get_referral_rewards(referrer, self.referral_reward_per_share);
+        info.volume += volume;
+        info.referred_shares = info.referred_shares + added - removed;
+        info.reward_debt = self.pro_rata(info.referred_shares, self.referral_reward_per_share, REFERRAL_PRECISION);
+
+        self.total_referred_shares = self.total_referred_shares + added - removed;
+        self.referrers.insert(referrer, &info);
+    }
+
+    /// Attributes newly minted sAZERO of `user` to `referrer`
+    /// sAZERO previously attributed to another referrer moves to the latest referrer
+    /// Must be called after `update_fees()`
+    pub fn add_referred_shares(&mut self, user: AccountId, referrer: AccountId, shares: Balance, azero: Balance) {
+        let mut held: Balance = 0;
+        let mut moved: Balance = 0;
+        if let Some(stake) = self.referred_stakes.get(user) {
+            if stake.referrer == referrer {
+                held = stake.shares;
+            } else {
+                self.adjust_referrer(stake.referrer, 0, stake.shares, 0);
+                moved = stake.shares;
+            }
+        }
+
+        self.adjust_referrer(referrer, shares + moved, 0, azero);
+        self.referred_stakes.insert(user, &ReferredStake {
+            referrer,
+            shares: held + moved + shares,
+        });
+    }
+
+    /// Removes sAZERO of `user` leaving the protocol from its referrer's attribution
+    /// Must be called after `update_fees()`
+    ///
+    /// Returns the referrer and the sAZERO removed from its attribution
+    pub fn remove_referred_shares(&mut self, user: AccountId, shares: Balance) -> Option<ReferredStake> {
+        let mut stake = self.referred_stakes.get(user)?;
+        let removed = if shares < stake.shares { shares } else { stake.shares };
+        self.adjust_referrer(stake.referrer, 0, removed, 0);
+
+        stake.shares -= removed;
+        if stake.shares == 0 {
+            self.referred_stakes.remove(user);
+        } else {
+            self.referred_stakes.insert(user, &stake);
+        }
+
+        Some(ReferredStake {
+            referrer: stake.referrer,
+            shares: removed,
+        })
+    }
+
+    /// Re-attributes cancelled sAZERO of an unlock request to the referrer it was removed from
+    /// Must be called after `update_fees()`
+    pub fn restore_referred_shares(&mut self, user: AccountId, unlock_id: u128, shares: Balance) {
+        if let Some(mut removed) = self.unlock_referrals.get(unlock_id) {
+            let restored = if shares < removed.shares { shares } else { removed.shares };
+            self.add_referred_shares(user, removed.referrer, restored, 0);
+
+            removed.shares -= restored;
+            if removed.shares == 0 {
+                self.unlock_referrals.remove(unlock_id);
+            } else {
+                self.unlock_referrals.insert(unlock_id, &removed);
+            }
+        }
+    }
+
+    /// Settles and clears a referrer's unclaimed rewards, releasing them from the reserved virtual shares
+    /// Must be called after `update_fees()`
+    ///
+    /// Returns the virtual shares to be minted to the referrer
+    pub fn take_referral_rewards(&mut self, referrer: AccountId) -> Balance {
+        self.adjust_referrer(referrer, 0, 0, 0);
+
+        let mut info = self.referrers.get(referrer).unwrap_or_default();
+        let rewards = if info.unclaimed < self.referral_shares_reserved { info.unclaimed } else { self.referral_shares_reserved };
+
+        info.unclaimed = 0;
+        self.referrers.insert(referrer, &info);
+        self.referral_shares_reserved -= rewards;
+        self.total_shares_virtual -= rewards;
+
+        rewards
+    }
+
     /// Returns the virtual shares that will exist at the given time
     pub fn get_virtual_shares_at_time(&self, current_time: Timestamp) -> Balance {
         // Time since last update
@@ -457,3 +1706,24 @@ impl VaultData {
         BigUint::to_u128(&result).unwrap()
     }
 }
+
+/// Verifies `account` is a leaf of the Merkle tree with `root`
+/// Leaves are the Blake2x256 hash of the account, pairs are hashed in sorted order
+pub fn verify_merkle_proof(root: [u8; 32], account: AccountId, proof: &[[u8; 32]]) -> bool {
+    let mut node = [0u8; 32];
+    hash_bytes::<Blake2x256>(account.as_ref(), &mut node);
+
+    for sibling in proof {
+        let mut pair = [0u8; 64];
+        if node <= *sibling {
+            pair[..32].copy_from_slice(&node);
+            pair[32..].copy_from_slice(sibling);
+        } else {
+            pair[..32].copy_from_slice(sibling);
+            pair[32..].copy_from_slice(&node);
+        }
+        hash_bytes::<Blake2x256>(&pair, &mut node);
+    }
+
+    node == root
+}
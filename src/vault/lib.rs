@@ -10,7 +10,7 @@ mod vault {
     use crate::traits::*;
 
     use ink::{
-        codegen::EmitEvent,
+        codegen::{EmitEvent, StaticEnv},
         contract_ref,
         env::Error as InkEnvError,
         prelude::{format, string::String, vec::Vec},
@@ -35,6 +35,8 @@ mod vault {
     pub struct Staked {
         #[ink(topic)]
         staker: AccountId,
+        #[ink(topic)]
+        beneficiary: AccountId,
         azero: Balance,
         new_shares: Balance,
         virtual_shares: Balance,
@@ -44,13 +46,35 @@ mod vault {
         #[ink(topic)]
         referral_id: AccountId,
         staker: AccountId,
+        beneficiary: AccountId,
         azero: Balance,
     }
     #[ink(event)]
+    pub struct ReferralRewardsClaimed {
+        #[ink(topic)]
+        referral_id: AccountId,
+        shares: Balance,
+    }
+    #[ink(event)]
+    pub struct ReferralWhitelistToggled {
+        enabled: bool,
+    }
+    #[ink(event)]
+    pub struct ReferrerRegistered {
+        #[ink(topic)]
+        referral_id: AccountId,
+    }
+    #[ink(event)]
+    pub struct ReferrerUnregistered {
+        #[ink(topic)]
+        referral_id: AccountId,
+    }
+    #[ink(event)]
     pub struct Compounded {
         caller: AccountId,
         azero: Balance,
         incentive: Balance,
+        performance_fee: Balance,
         virtual_shares: Balance,
     }
     #[ink(event)]
@@ -70,6 +94,23 @@ mod vault {
         unlock_id: u128,
     }
     #[ink(event)]
+    pub struct UnlockRequestApproved {
+        #[ink(topic)]
+        staker: AccountId,
+        operator: Option<AccountId>,
+        unlock_id: u128,
+    }
+    #[ink(event)]
+    pub struct UnlockRequestTransferred {
+        #[ink(topic)]
+        from: AccountId,
+        #[ink(topic)]
+        to: AccountId,
+        shares: Balance,
+        batch_id: u64,
+        unlock_id: u128,
+    }
+    #[ink(event)]
     pub struct BatchUnlockSent {
         #[ink(topic)]
         batch_id: u64,
@@ -83,41 +124,229 @@ mod vault {
         staker: AccountId,
         azero: Balance,
         batch_id: u64,
-        unlock_id: u64,
+        unlock_id: u128,
+    }
+    #[ink(event)]
+    pub struct InstantUnlocked {
+        #[ink(topic)]
+        staker: AccountId,
+        shares: Balance,
+        azero: Balance,
+        fee: Balance,
+    }
+    #[ink(event)]
+    pub struct BufferReplenished {
+        azero: Balance,
+        liquidity_buffer: Balance,
+    }
+    #[ink(event)]
+    pub struct UnlocksRedeemed {
+        #[ink(topic)]
+        staker: AccountId,
+        azero: Balance,
+        count: u128,
     }
     #[ink(event)]
     pub struct FeesWithdrawn {
+        #[ink(topic)]
+        recipient: AccountId,
+        shares: Balance,
+    }
+    #[ink(event)]
+    pub struct InsuranceToppedUp {
+        shares: Balance,
+        azero: Balance,
+    }
+    #[ink(event)]
+    pub struct InsuranceDrawn {
         shares: Balance,
+        azero: Balance,
+    }
+    #[ink(event)]
+    pub struct Donated {
+        #[ink(topic)]
+        donor: AccountId,
+        azero: Balance,
+        release_end: Timestamp,
+    }
+    #[ink(event)]
+    pub struct DonationDurationAdjusted {
+        new_donation_duration: u64,
+    }
+    #[ink(event)]
+    pub struct RewardVestingPeriodAdjusted {
+        new_reward_vesting_period: u64,
+    }
+    #[ink(event)]
+    pub struct InsurancePercentagesAdjusted {
+        new_fee_percentage: u16,
+        new_incentive_percentage: u16,
+    }
+    #[ink(event)]
+    pub struct FeeRecipientsUpdated {
+        recipients: Vec<FeeRecipient>,
     }
     #[ink(event)]
-    pub struct FeesAdjusted {
-        new_fee: u16,
+    pub struct FeeChangeQueued {
+        kind: FeeKind,
+        new_value: u16,
+        effective_time: Timestamp,
+    }
+    #[ink(event)]
+    pub struct FeeChangeApplied {
+        kind: FeeKind,
+        new_value: u16,
         virtual_shares: Balance,
     }
     #[ink(event)]
-    pub struct IncentiveAdjusted {
-        new_incentive: u16,
+    pub struct FeeChangeCancelled {
+        kind: FeeKind,
+        new_value: u16,
+    }
+    #[ink(event)]
+    pub struct FeeChangeLimitsQueued {
+        new_delay: u64,
+        new_period: u64,
+        new_max_change: u16,
+        effective_time: Timestamp,
+    }
+    #[ink(event)]
+    pub struct FeeChangeLimitsAdjusted {
+        new_delay: u64,
+        new_period: u64,
+        new_max_change: u16,
     }
     #[ink(event)]
     pub struct MinimumStakeAdjusted {
         new_minimum_stake: Balance,
     }
     #[ink(event)]
-    pub struct OwnershipTransferred {
+    pub struct MaxTotalPooledAdjusted {
+        new_max_total_pooled: Balance,
+    }
+    #[ink(event)]
+    pub struct MaxAccountStakeAdjusted {
+        new_max_account_stake: Option<Balance>,
+    }
+    #[ink(event)]
+    pub struct BufferTargetAdjusted {
+        new_buffer_target: u16,
+    }
+    #[ink(event)]
+    pub struct MaxInstantUnlockAdjusted {
+        new_max_instant_unlock: Balance,
+    }
+    #[ink(event)]
+    pub struct KeeperRewarded {
+        #[ink(topic)]
+        keeper: AccountId,
+        azero: Balance,
+        shares: Balance,
+    }
+    #[ink(event)]
+    pub struct KeeperRewardAdjusted {
+        new_keeper_reward: KeeperReward,
+    }
+    #[ink(event)]
+    pub struct CheckpointGranularityAdjusted {
+        new_checkpoint_granularity: u64,
+    }
+    #[ink(event)]
+    pub struct MaxBatchesPerDispatchAdjusted {
+        new_max_batches_per_dispatch: u32,
+    }
+    #[ink(event)]
+    pub struct AllowlistAdded {
+        #[ink(topic)]
+        account: AccountId,
+    }
+    #[ink(event)]
+    pub struct AllowlistRemoved {
+        #[ink(topic)]
+        account: AccountId,
+    }
+    #[ink(event)]
+    pub struct AllowlistToggled {
+        enabled: bool,
+    }
+    #[ink(event)]
+    pub struct AllowlistMerkleRootUpdated {
+        merkle_root: Option<[u8; 32]>,
+    }
+    #[ink(event)]
+    pub struct RoleTransferProposed {
+        role_type: RoleType,
+        new_account: AccountId,
+    }
+    #[ink(event)]
+    pub struct RoleAdminTransferProposed {
+        role_type: RoleType,
         new_account: AccountId,
     }
     #[ink(event)]
-    pub struct RoleSetFeesTransferred {
+    pub struct RoleTransferCancelled {
+        role_type: RoleType,
+    }
+    #[ink(event)]
+    pub struct RoleAccountChanged {
+        role_type: RoleType,
         new_account: AccountId,
     }
     #[ink(event)]
-    pub struct RoleSetFeesAdminTransferred {
+    pub struct RoleAdminChanged {
+        role_type: RoleType,
         new_account: AccountId,
     }
     #[ink(event)]
-    pub struct NewHash {
+    pub struct RoleRenounced {
+        role_type: RoleType,
+        account: AccountId,
+    }
+    #[ink(event)]
+    pub struct RoleAdminRenounced {
+        role_type: RoleType,
+        account: AccountId,
+    }
+    #[ink(event)]
+    pub struct PauseToggled {
+        switch: PauseSwitch,
+        paused: bool,
+    }
+    #[ink(event)]
+    pub struct GuardianPaused {
+        loss: Balance,
+    }
+    #[ink(event)]
+    pub struct SlashDetected {
+        total_loss: Balance,
+        total_recovered: Balance,
+        agents: Vec<AgentLoss>,
+    }
+    #[ink(event)]
+    pub struct AgentLossReported {
+        #[ink(topic)]
+        agent: AccountId,
+        azero: Balance,
+    }
+    #[ink(event)]
+    pub struct CodeProposed {
+        code_hash: [u8; 32],
+        effective_time: Timestamp,
+    }
+    #[ink(event)]
+    pub struct CodeCancelled {
         code_hash: [u8; 32],
     }
+    #[ink(event)]
+    pub struct CodeUpgraded {
+        old_code_hash: [u8; 32],
+        new_code_hash: [u8; 32],
+    }
+    #[ink(event)]
+    pub struct Migrated {
+        from_version: u32,
+        to_version: u32,
+    }
 
     #[ink(storage)]
     pub struct Vault {
@@ -132,6 +361,15 @@ mod vault {
             emitter.emit_event(event);
         }
 
+        /// Fails while upgraded code is awaiting its storage migration via `migrate()`
+        fn check_version(&self) -> Result<(), VaultError> {
+            if self.data.version < VAULT_VERSION {
+                return Err(VaultError::MigrationPending);
+            }
+
+            Ok(())
+        }
+
         fn transfer_shares_from(
             &self,
             from: &AccountId,
@@ -145,6 +383,21 @@ mod vault {
             Ok(())
         }
 
+        /// Transfers sAZERO consuming the allowance `from` has granted to `spender`
+        fn spender_transfer_shares_from(
+            &self,
+            spender: &AccountId,
+            from: &AccountId,
+            to: &AccountId,
+            amount: Balance,
+        ) -> Result<(), VaultError> {
+            let mut token: contract_ref!(ShareToken) = self.data.shares_contract.into();
+            if let Err(e) = token.spender_transfer_from(*spender, *from, *to, amount) {
+                return Err(VaultError::TokenError(e));
+            }
+            Ok(())
+        }
+
         fn transfer_shares_to(&self, to: &AccountId, amount: &Balance) -> Result<(), VaultError> {
             let mut token: contract_ref!(PSP22) = self.data.shares_contract.into();
             if let Err(e) = token.transfer(*to, *amount, Vec::new()) {
@@ -170,71 +423,14 @@ mod vault {
             }
             Ok(())
         }
-    }
-
-    impl RateProvider for Vault {
-        /// Calculate the value of sAZERO in terms of AZERO with TARGET_DECIMALS precision
-        #[ink(message)]
-        fn get_rate(&mut self) -> u128 {
-            // Because both RATE_DECIMALS and sAZERO.decimals() are 12,
-            // no further adjustment is necessary
-            self.get_azero_from_shares(1e12 as u128)
-        }
-    }
-
-    impl Vault {
-        #[ink(constructor)]
-        pub fn new(
-            share_token_hash: Hash,
-            registry_code_hash: Hash,
-            nomination_agent_hash: Hash,
-        ) -> Self {
-            Self::custom_era(share_token_hash, registry_code_hash, nomination_agent_hash, DAY)
-        }
-
-        #[ink(constructor)]
-        pub fn custom_era(
-            share_token_hash: Hash,
-            registry_code_hash: Hash,
-            nomination_agent_hash: Hash,
-            era: u64,
-        ) -> Self {
-            let caller = Self::env().caller();
-            let now = Self::env().block_timestamp();
-
-            let registry_ref = RegistryRef::new(caller, caller, caller, nomination_agent_hash)
-                .endowment(0)
-                .code_hash(registry_code_hash)
-                .salt_bytes(
-                    &[9_u8.to_le_bytes().as_ref(), caller.as_ref()].concat()[..4],
-                )
-                .instantiate();
-            let share_token_ref = TokenRef::new(Some(String::from("sAZERO")), Some(String::from("SAZ")))
-                .endowment(0)
-                .code_hash(share_token_hash)
-                .salt_bytes(
-                    &[7_u8.to_le_bytes().as_ref(), caller.as_ref()].concat()[..4],
-                )
-                .instantiate();
 
-            Self {
-                data: VaultData::new(
-                    caller,
-                    TokenRef::to_account_id(&share_token_ref),
-                    registry_ref,
-                    now,
-                    era,
-                ),
+        /// Mints the beneficiary sAZERO for the transferred AZERO
+        /// See `stake_for()`
+        fn deposit(&mut self, beneficiary: AccountId) -> Result<Balance, VaultError> {
+            if self.data.paused.staking {
+                return Err(VaultError::Paused);
             }
-        }
 
-        /// Allow users to convert AZERO into sAZERO
-        /// Mints the caller sAZERO based on the redemption ratio
-        ///
-        /// Minimum AZERO amount is required to stake
-        /// AZERO must be transferred via transferred_value
-        #[ink(message, payable)]
-        pub fn stake(&mut self) -> Result<Balance, VaultError> {
             let caller = Self::env().caller();
             let azero = Self::env().transferred_value();
 
@@ -242,55 +438,59 @@ mod vault {
             if azero < self.data.minimum_stake {
                 return Err(VaultError::MinimumStake);
             }
+            // Verify staking caps are not exceeded
+            if azero > self.data.get_remaining_capacity() {
+                return Err(VaultError::MaximumTotalPooled);
+            }
+            if azero > self.data.get_account_remaining_capacity(beneficiary) {
+                return Err(VaultError::MaximumAccountStake);
+            }
+
+            self.auto_dispatch_batches();
 
             // Update fees before calculating redemption ratio and minting shares
             self.data.update_fees(Self::env().block_timestamp());
 
             // Handle sAZERO
             let new_shares = self.get_shares_from_azero(azero);
-            self.mint_shares(new_shares, caller)?;
+            if new_shares == 0 {
+                return Err(VaultError::ZeroShares);
+            }
+            self.mint_shares(new_shares, beneficiary)?;
 
             // Handle AZERO
-            self.data.delegate_bonding(azero)?;
+            let buffered = self.data.get_buffer_deposit(azero);
+            self.data.delegate_bonding(azero - buffered)?;
+            self.data.liquidity_buffer += buffered;
+            self.data.add_net_staked(beneficiary, azero);
 
             Self::emit_event(
                 Self::env(),
                 Event::Staked(Staked {
                     staker: caller,
+                    beneficiary,
                     azero,
                     new_shares,
                     virtual_shares: self.data.total_shares_virtual, // updated in update_fees()
                 }),
             );
 
-            Ok(new_shares)
-        }
+            self.record_rate_checkpoint();
 
-        #[ink(message, payable)]
-        pub fn stake_with_referral(&mut self, referral_id: AccountId) -> Result<Balance, VaultError> {
-            let new_shares = self.stake()?;
-            Self::emit_event(
-                Self::env(),
-                Event::Referral(Referral {
-                    referral_id,
-                    staker: Self::env().caller(),
-                    azero: Self::env().transferred_value(),
-                }),
-            );
             Ok(new_shares)
         }
 
-        /// Allow user to begin the unlock process
-        /// Transfers sAZERO specified in `shares` argument to the vault contract
-        /// Unlock is batched into current two era batch request
+        /// Batches an unlock request into the current two era batch request on behalf of `user`
+        /// Requested sAZERO must already be held by the vault contract
         ///
-        /// Caller must approve the psp22 token contract beforehand
-        #[ink(message)]
-        pub fn request_unlock(&mut self, shares: Balance) -> Result<(), VaultError> {
-            let caller = Self::env().caller();
+        /// Returns the unlock id
+        fn create_unlock_request(&mut self, user: AccountId, shares: Balance) -> Result<u128, VaultError> {
             let now = Self::env().block_timestamp();
 
-            self.transfer_shares_from(&caller, &Self::env().account_id(), shares)?;
+            // Unlocked sAZERO no longer earns referral rewards or counts towards the account stake cap
+            self.data.update_fees(now);
+            let removed_referral = self.data.remove_referred_shares(user, shares);
+            self.data.sub_net_staked(user, self.get_azero_from_shares(shares));
 
             let current_batch_unlock_id = self.data.get_batch_unlock_id(now);
             let current_batch_unlock_shares = self
@@ -310,85 +510,135 @@ mod vault {
                 },
             );
 
-            // Update user's unlock requests
-            let mut user_unlock_requests = self.data.user_unlock_requests.get(caller).unwrap_or(Vec::new());
-            user_unlock_requests.push(UnlockRequest {
+            // Record user's unlock request
+            let unlock_id = self.data.add_unlock_request(&UnlockRequest {
+                owner: user,
                 creation_time: now,
                 share_amount: shares,
                 batch_id: current_batch_unlock_id,
+                operator: None,
             });
-            self.data.user_unlock_requests.insert(caller, &user_unlock_requests);
+            if let Some(removed_referral) = removed_referral {
+                self.data.unlock_referrals.insert(unlock_id, &removed_referral);
+            }
 
             Self::emit_event(
                 Self::env(),
                 Event::UnlockRequested(UnlockRequested {
-                    staker: caller,
+                    staker: user,
                     shares,
-                    unlock_id: (user_unlock_requests.len()-1) as u128,
+                    unlock_id,
                     batch_id: current_batch_unlock_id,
                 }),
             );
 
-            Ok(())
+            self.record_rate_checkpoint();
+
+            Ok(unlock_id)
         }
 
-        /// Allow user to cancel their unlock request
+        /// Removes shares from an unlock request and its batch unlock request, returning them to the user
         ///
-        /// Must be done in the same batch interval in which the request was originally sent
-        #[ink(message)]
-        pub fn cancel_unlock_request(&mut self, user_unlock_id: u128) -> Result<(), VaultError> {
-            let caller = Self::env().caller();
-            let now = Self::env().block_timestamp();
-
-            let current_batch_unlock_id = self.data.get_batch_unlock_id(now);
-            let mut user_unlock_requests = self.data.user_unlock_requests.get(caller).unwrap_or(Vec::new());
+        /// `shares` - Amount to cancel; `None` cancels the entire unlock request
+        fn cancel_unlock_shares(
+            &mut self,
+            user: AccountId,
+            unlock_id: u128,
+            mut unlock_request: UnlockRequest,
+            shares: Option<Balance>,
+        ) -> Result<(), VaultError> {
+            let batch_id = unlock_request.batch_id;
+            let share_amount = shares.unwrap_or(unlock_request.share_amount);
 
-            if user_unlock_id >= user_unlock_requests.len() as u128 {
+            if share_amount == 0 {
+                return Err(VaultError::NoChange);
+            }
+            if share_amount > unlock_request.share_amount {
                 return Err(VaultError::InvalidUserUnlockRequest);
             }
 
-            if current_batch_unlock_id != user_unlock_requests[user_unlock_id as usize].batch_id {
+            // Cannot cancel once the batch unlock request has been sent
+            let mut batch = self.data.batch_unlock_requests.get(batch_id).unwrap();
+            if batch.redemption_timestamp.is_some() {
                 return Err(VaultError::InvalidBatchUnlockRequest);
             }
 
-            let share_amount = user_unlock_requests[user_unlock_id as usize].share_amount.clone();
+            // Returned sAZERO earns referral rewards again
+            self.data.update_fees(Self::env().block_timestamp());
+            self.data.restore_referred_shares(user, unlock_id, share_amount);
 
-            // Delete user's cancelled unlock request
-            user_unlock_requests.remove(user_unlock_id as usize);
-            self.data.user_unlock_requests.insert(caller, &user_unlock_requests);
+            // Update or delete user's cancelled unlock request
+            if share_amount == unlock_request.share_amount {
+                self.data.remove_unlock_request(unlock_id);
+            } else {
+                unlock_request.share_amount -= share_amount;
+                self.data.unlock_requests.insert(unlock_id, &unlock_request);
+            }
 
-            // Remove shares from current batch unlock request
-            let mut current_batch = self.data.batch_unlock_requests.get(current_batch_unlock_id).unwrap();
-            current_batch.total_shares -= share_amount;
-            self.data.batch_unlock_requests.insert(current_batch_unlock_id, &current_batch);
+            // Remove shares from batch unlock request
+            batch.total_shares -= share_amount;
+            self.data.batch_unlock_requests.insert(batch_id, &batch);
 
-            // Return shares to caller
-            self.transfer_shares_to(&caller, &share_amount)?;
+            // Return shares to user
+            self.transfer_shares_to(&user, &share_amount)?;
+            self.data.add_net_staked(user, self.get_azero_from_shares(share_amount));
 
             Self::emit_event(
                 Self::env(),
                 Event::UnlockCanceled(UnlockCanceled {
-                    staker: caller,
+                    staker: user,
                     shares: share_amount,
-                    unlock_id: user_unlock_id,
-                    batch_id: current_batch_unlock_id,
+                    unlock_id,
+                    batch_id,
                 }),
             );
 
+            self.record_rate_checkpoint();
+
             Ok(())
         }
 
-        /// Trigger unlock requests of previous batched requests
-        /// Distributes unlock requests to nominators according to current stake imbalances
-        /// Calculates a batch spot values for sAZERO in the batches
-        /// Burns associated sAZERO
-        ///
-        /// Cannot be called for a batch that has not concluded
-        /// Cannot be called for a batch that has already been redeemed
-        /// Batch IDs must be specified in ascending order (for gas efficient duplicate check)
-        #[ink(message)]
-        pub fn send_batch_unlock_requests(&mut self, batch_ids: Vec<u64>) -> Result<(), VaultError> {
-            let now = Self::env().block_timestamp();
+        /// Moves an unlock request from one user to another
+        /// Re-indexes both users' unlock requests and clears any operator approval
+        fn move_unlock_request(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            unlock_id: u128,
+        ) -> Result<(), VaultError> {
+            if from == to {
+                return Err(VaultError::NoChange);
+            }
+
+            let mut unlock_request = self.data.get_user_unlock_request(from, unlock_id)?;
+            unlock_request.owner = to;
+            unlock_request.operator = None;
+            self.data.unlock_requests.insert(unlock_id, &unlock_request);
+
+            self.data.unindex_unlock_request(from, unlock_id);
+            self.data.index_unlock_request(to, unlock_id);
+
+            Self::emit_event(
+                Self::env(),
+                Event::UnlockRequestTransferred(UnlockRequestTransferred {
+                    from,
+                    to,
+                    shares: unlock_request.share_amount,
+                    batch_id: unlock_request.batch_id,
+                    unlock_id,
+                }),
+            );
+
+            Ok(())
+        }
+
+        /// Sends the batch unlock requests specified by `batch_ids`
+        /// See `send_batch_unlock_requests()`
+        /// Returns the aggregate spot value of the batches
+        fn send_batches(&mut self, batch_ids: Vec<u64>, now: Timestamp) -> Result<Balance, VaultError> {
+            if self.data.paused.batch_sending {
+                return Err(VaultError::Paused);
+            }
 
             let current_batch_unlock_id = self.data.get_batch_unlock_id(now);
 
@@ -418,15 +668,24 @@ mod vault {
             self.data.update_fees(now);
 
             let total_shares_virtual_ = self.data.total_shares_virtual; // shadow
-            let mut aggregate_batch_spot_value: Balance = 0;
-            let mut aggregate_total_shares: Balance = 0;
+            let batch_spot_values: Vec<Balance> = batches
+                .iter()
+                .map(|batch| self.get_azero_from_shares(batch.total_shares))
+                .collect();
+            let aggregate_batch_spot_value: Balance = batch_spot_values.iter().sum();
+            let aggregate_total_shares: Balance = batches.iter().map(|batch| batch.total_shares).sum();
 
-            for (batch_id, mut batch) in batch_ids.into_iter().zip(batches.into_iter()) {
-                let batch_spot_value = self.get_azero_from_shares(batch.total_shares);
+            // Replenish liquidity buffer alongside the unlock requests
+            let buffer_refill = self.data.get_buffer_refill(aggregate_batch_spot_value);
 
-                aggregate_batch_spot_value += batch_spot_value;
-                aggregate_total_shares += batch.total_shares;
+            // Allocate unlock quantity across nomination pools
+            // Batches are only marked as sent once unbonding succeeds
+            self.data.delegate_unbonding(aggregate_batch_spot_value + buffer_refill)?;
+            if buffer_refill > 0 {
+                self.data.add_buffer_unbonding(buffer_refill, now);
+            }
 
+            for ((batch_id, mut batch), batch_spot_value) in batch_ids.into_iter().zip(batches).zip(batch_spot_values) {
                 // Update batch request
                 batch.value_at_redemption = Some(batch_spot_value);
                 batch.redemption_timestamp = Some(now);
@@ -444,343 +703,2428 @@ mod vault {
                 );
             }
 
-            // Allocate unlock quantity across nomination pools
-            self.data.delegate_unbonding(aggregate_batch_spot_value)?;
-
             self.burn_shares(aggregate_total_shares)?;
 
-            Ok(())
+            self.record_rate_checkpoint();
+
+            Ok(aggregate_batch_spot_value)
         }
 
+        /// Determines concluded batch unlock requests which have not been sent
+        /// Advances `oldest_unsent_batch_id` past batches which are empty, already sent, or returned
+        /// Scans at most `MAX_BATCH_SCAN` batch ids per call
+        ///
+        /// `limit` - Maximum number of batch ids to return; `None` is bounded by the scan only
+        fn collect_pending_batches(&mut self, now: Timestamp, limit: Option<u32>) -> Vec<u64> {
+            let current_batch_unlock_id = self.data.get_batch_unlock_id(now);
 
-        /// Attempts to claim unbonded AZERO from all validators
-        #[ink(message)]
-        pub fn delegate_withdraw_unbonded(&mut self) -> Result<(), VaultError> {
+            let mut batch_ids: Vec<u64> = Vec::new();
+            let mut batch_id = self.data.oldest_unsent_batch_id;
+            let scan_end = current_batch_unlock_id.min(batch_id + MAX_BATCH_SCAN);
+
+            while batch_id < scan_end {
+                if let Some(limit_) = limit {
+                    if batch_ids.len() as u32 >= limit_ {
+                        break;
+                    }
+                }
+                if let Some(batch) = self.data.batch_unlock_requests.get(batch_id) {
+                    if batch.redemption_timestamp.is_none() && batch.total_shares > 0 {
+                        batch_ids.push(batch_id);
+                    }
+                }
+                batch_id += 1;
+            }
+
+            self.data.oldest_unsent_batch_id = batch_id;
+
+            batch_ids
+        }
+
+        /// Sends concluded batch unlock requests which have not been sent
+        ///
+        /// `limit` - Maximum number of batches to send; `None` is unbounded
+        /// Returns the number of batches sent and their aggregate spot value
+        fn dispatch_pending_batches(&mut self, limit: Option<u32>) -> Result<(u32, Balance), VaultError> {
+            let now = Self::env().block_timestamp();
+
+            let batch_ids = self.collect_pending_batches(now, limit);
+            let count = batch_ids.len() as u32;
+
+            if count == 0 {
+                return Ok((0, 0));
+            }
+
+            let processed = self.send_batches(batch_ids, now)?;
+
+            Ok((count, processed))
+        }
+
+        /// Lazily sends pending batch unlock requests during user interactions
+        /// Disabled when `max_batches_per_dispatch` is zero or batch sending is paused
+        /// A failed dispatch is skipped, leaving the batches pending for a later dispatch
+        fn auto_dispatch_batches(&mut self) {
+            let max_batches_per_dispatch_ = self.data.max_batches_per_dispatch; // shadow
+            if max_batches_per_dispatch_ == 0 || self.data.paused.batch_sending {
+                return;
+            }
+
+            let oldest_unsent_batch_id_ = self.data.oldest_unsent_batch_id; // shadow
+            if self.dispatch_pending_batches(Some(max_batches_per_dispatch_)).is_err() {
+                self.data.oldest_unsent_batch_id = oldest_unsent_batch_id_;
+            }
+        }
+
+        /// Pauses staking, unlock requests, batch sending, and redemption upon a loss of bonded AZERO
+        fn guardian_pause(&mut self, loss: Balance) {
+            self.data.paused.staking = true;
+            self.data.paused.unlock_requests = true;
+            self.data.paused.batch_sending = true;
+            self.data.paused.redemption = true;
+
+            Self::emit_event(
+                Self::env(),
+                Event::GuardianPaused(GuardianPaused {
+                    loss,
+                }),
+            );
+        }
+
+        /// Determines the status, AZERO value, and claimable time of an unlock request
+        fn get_unlock_request_details_of(
+            &self,
+            unlock_id: u128,
+            unlock_request: &UnlockRequest,
+            now: Timestamp,
+        ) -> UnlockRequestDetails {
+            let batch = self.data.batch_unlock_requests.get(unlock_request.batch_id).unwrap();
+
+            let (status, azero, claimable_at) = match (batch.redemption_timestamp, batch.value_at_redemption) {
+                (Some(redemption_timestamp), Some(value_at_redemption)) => {
+                    let claimable_at = redemption_timestamp + self.data.cooldown_period;
+                    let status = if now >= claimable_at {
+                        UnlockRequestStatus::Claimable
+                    } else {
+                        UnlockRequestStatus::Cooldown
+                    };
+                    let azero = self.data.pro_rata(
+                        unlock_request.share_amount,
+                        value_at_redemption,
+                        batch.total_shares,
+                    );
+                    (status, azero, Some(claimable_at))
+                },
+                _ => (
+                    UnlockRequestStatus::Pending,
+                    self.get_azero_from_shares(unlock_request.share_amount),
+                    None,
+                ),
+            };
+
+            UnlockRequestDetails {
+                unlock_id,
+                share_amount: unlock_request.share_amount,
+                batch_id: unlock_request.batch_id,
+                status,
+                azero,
+                claimable_at,
+            }
+        }
+
+        /// Claims unbonded AZERO from all agents into the Vault
+        ///
+        /// Withdrawn AZERO is credited to the liquidity buffer up to its claimable unbonding chunks,
+        /// the remainder is held for redemptions
+        /// Returns the AZERO withdrawn
+        fn withdraw_unbonded(&mut self) -> Result<Balance, VaultError> {
+            let balance_before = Self::env().balance();
             self.data.delegate_withdraw_unbonded()?;
+            let withdrawn = Self::env().balance() - balance_before;
 
-            Ok(())
+            let replenished = self.data.replenish_buffer(withdrawn, Self::env().block_timestamp());
+
+            if replenished > 0 {
+                Self::emit_event(
+                    Self::env(),
+                    Event::BufferReplenished(BufferReplenished {
+                        azero: replenished,
+                        liquidity_buffer: self.data.liquidity_buffer,
+                    }),
+                );
+            }
+
+            self.record_rate_checkpoint();
+
+            Ok(withdrawn)
         }
 
-        /// Allows a user to withdraw staked AZERO
+        /// Queues a fee change to be applied after `fee_change_delay`
+        /// Applies the change immediately when there is no delay
         ///
-        /// Returns original deposit amount plus interest to depositor address
-        /// Queries the redeemable amount by user AccountId and Claim Vector index
-        /// Associated batch unlock request must have been completed
-        /// Deletes the user's unlock request
-        /// Burns the associated sAZERO tokens
-        #[ink(message)]
-        pub fn redeem(&mut self, user: AccountId, unlock_id: u64) -> Result<(), VaultError> {
+        /// Caller must have the adjust fee role (`RoleType::AdjustFee`)
+        /// Cannot exceed the hard ceiling of the fee kind
+        /// Cannot change the fee by more than `max_fee_change`
+        /// Cannot take effect within `fee_change_period` of the previous change
+        fn queue_fee_change(&mut self, kind: FeeKind, new_value: u16) -> Result<(), VaultError> {
+            let caller = Self::env().caller();
             let now = Self::env().block_timestamp();
 
-            let mut user_unlock_requests = self.data.user_unlock_requests.get(user).unwrap();
+            if caller != self.data.get_role(RoleType::AdjustFee) {
+                return Err(VaultError::InvalidPermissions);
+            }
 
-            // Ensure user specified a valid unlock request index
-            if unlock_id >= user_unlock_requests.len() as u64 {
-                return Err(VaultError::InvalidUserUnlockRequest);
+            let current_value = self.data.get_fee_value(kind);
+            if current_value == new_value {
+                return Err(VaultError::NoChange);
+            }
+            if new_value > self.data.get_fee_ceiling(kind) {
+                return Err(VaultError::FeeCeiling);
+            }
+            if current_value.abs_diff(new_value) > self.data.max_fee_change {
+                return Err(VaultError::FeeChangeLimit);
+            }
+            if self.data.fee_change_proposals.contains(kind) {
+                return Err(VaultError::FeeChangePending);
             }
 
-            let batch_id = user_unlock_requests[unlock_id as usize].batch_id;
-            let share_amount = user_unlock_requests[unlock_id as usize].share_amount;
+            let effective_time = now + self.data.fee_change_delay;
+            if let Some(last_change) = self.data.last_fee_change.get(kind) {
+                if effective_time < last_change + self.data.fee_change_period {
+                    return Err(VaultError::FeeChangeLimit);
+                }
+            }
 
-            // Ensure batch unlock has been redeemed
-            let batch_unlock_request = self.data.batch_unlock_requests.get(batch_id).unwrap();
-            if batch_unlock_request.redemption_timestamp.is_none() || batch_unlock_request.value_at_redemption.is_none() {
-                return Err(VaultError::InvalidBatchUnlockRequest);
+            self.data.fee_change_proposals.insert(kind, &FeeChangeProposal {
+                new_value,
+                effective_time,
+            });
+
+            Self::emit_event(
+                Self::env(),
+                Event::FeeChangeQueued(FeeChangeQueued {
+                    kind,
+                    new_value,
+                    effective_time,
+                }),
+            );
+
+            if effective_time == now {
+                self.apply_queued_fee_change(kind)?;
+            }
+
+            Ok(())
+        }
+
+        /// Applies a queued fee change which has reached its effective time
+        /// Updates the total_shares_virtual accumulator at the old fee level first
+        fn apply_queued_fee_change(&mut self, kind: FeeKind) -> Result<(), VaultError> {
+            let now = Self::env().block_timestamp();
+
+            let proposal = self.data.fee_change_proposals.get(kind).ok_or(VaultError::NoFeeChangeQueued)?;
+            if now < proposal.effective_time {
+                return Err(VaultError::FeeChangeNotReady);
             }
 
-            // Ensure batch unlock has completed
-            let time_since_redemption = now - batch_unlock_request.redemption_timestamp.unwrap();
-            if time_since_redemption < self.data.cooldown_period {
-                return Err(VaultError::CooldownPeriod);
+            self.data.update_fees(now);
+            match kind {
+                FeeKind::Management => self.data.fee_percentage = proposal.new_value,
+                FeeKind::Incentive => self.data.incentive_percentage = proposal.new_value,
+                FeeKind::Performance => self.data.performance_fee_percentage = proposal.new_value,
+                FeeKind::InstantUnlock => self.data.instant_unlock_fee_percentage = proposal.new_value,
+                FeeKind::Referral => self.data.referral_fee_percentage = proposal.new_value,
             }
 
-            // Delete completed user unlock request
-            user_unlock_requests.remove(unlock_id as usize);
-            self.data.user_unlock_requests.insert(user, &user_unlock_requests);
+            self.data.fee_change_proposals.remove(kind);
+            self.data.last_fee_change.insert(kind, &now);
 
-            // Send AZERO to user
-            let azero = self.data.pro_rata(
-                share_amount,
-                batch_unlock_request.value_at_redemption.unwrap(),
-                batch_unlock_request.total_shares,
+            Self::emit_event(
+                Self::env(),
+                Event::FeeChangeApplied(FeeChangeApplied {
+                    kind,
+                    new_value: proposal.new_value,
+                    virtual_shares: self.data.total_shares_virtual, // updated in update_fees()
+                }),
             );
-            Self::env().transfer(user, azero)?;
+
+            self.record_rate_checkpoint();
+
+            Ok(())
+        }
+
+        /// Applies a queued change of the fee change limits which has reached its effective time
+        fn apply_queued_fee_change_limits(&mut self) -> Result<(), VaultError> {
+            let now = Self::env().block_timestamp();
+
+            let proposal = self.data.fee_change_limits_proposal.clone().ok_or(VaultError::NoFeeChangeQueued)?;
+            if now < proposal.effective_time {
+                return Err(VaultError::FeeChangeNotReady);
+            }
+
+            self.data.fee_change_delay = proposal.new_delay;
+            self.data.fee_change_period = proposal.new_period;
+            self.data.max_fee_change = proposal.new_max_change;
+            self.data.fee_change_limits_proposal = None;
 
             Self::emit_event(
                 Self::env(),
-                Event::UnlockRedeemed(UnlockRedeemed {
-                    staker: user,
-                    azero,
-                    unlock_id,
-                    batch_id,
+                Event::FeeChangeLimitsAdjusted(FeeChangeLimitsAdjusted {
+                    new_delay: proposal.new_delay,
+                    new_period: proposal.new_period,
+                    new_max_change: proposal.new_max_change,
                 }),
             );
 
             Ok(())
         }
 
-        /// Alternative method for a user to withdraw staked AZERO
+        /// Records the current redemption ratio into the rate checkpoint ring buffer
+        /// Skipped when the latest checkpoint is within `checkpoint_granularity`
+        fn record_rate_checkpoint(&mut self) {
+            let now = Self::env().block_timestamp();
+            let total_pooled = self.data.get_total_value();
+            let total_shares = self.get_total_shares();
+
+            self.data.add_rate_checkpoint(RateCheckpoint {
+                timestamp: now,
+                total_pooled,
+                total_shares,
+            });
+        }
+
+        /// Rewards a keeper for a maintenance call which processed `processed` AZERO
         ///
-        /// This should be called instead of `redeem()` when insufficient AZERO exists in the Vault and
-        /// validator(s) have unbonded AZERO which can be claimed
+        /// Reward is paid as sAZERO minted from accrued fees (virtual shares)
+        /// Reward is limited by the accrued fees and never exceeds the processed value
+        fn pay_keeper_reward(&mut self, keeper: AccountId, processed: Balance) -> Result<(), VaultError> {
+            let reward = self.data.get_keeper_reward(processed);
+            if reward == 0 {
+                return Ok(());
+            }
+
+            self.data.update_fees(Self::env().block_timestamp());
+
+            let mut shares = self.get_shares_from_azero(reward);
+            let withdrawable_fees = self.data.total_shares_virtual - self.data.referral_shares_reserved;
+            if shares > withdrawable_fees {
+                shares = withdrawable_fees;
+            }
+            if shares == 0 {
+                return Ok(());
+            }
+
+            let azero = self.get_azero_from_shares(shares);
+
+            self.mint_shares(shares, keeper)?;
+            self.data.total_shares_virtual -= shares;
+
+            Self::emit_event(
+                Self::env(),
+                Event::KeeperRewarded(KeeperRewarded {
+                    keeper,
+                    azero,
+                    shares,
+                }),
+            );
+
+            Ok(())
+        }
+    }
+
+    impl RateProvider for Vault {
+        /// Calculate the value of sAZERO in terms of AZERO with TARGET_DECIMALS precision
         #[ink(message)]
-        pub fn redeem_with_withdraw(&mut self, user: AccountId, unlock_id: u64) -> Result<(), VaultError> {
-            // Claim all unbonded AZERO into Vault
-            self.data.delegate_withdraw_unbonded()?;
+        fn get_rate(&mut self) -> u128 {
+            Vault::get_rate(self)
+        }
+    }
+
+    impl Vault {
+        #[ink(constructor)]
+        pub fn new(
+            share_token_hash: Hash,
+            registry_code_hash: Hash,
+            nomination_agent_hash: Hash,
+        ) -> Self {
+            Self::custom_era(share_token_hash, registry_code_hash, nomination_agent_hash, DAY)
+        }
+
+        #[ink(constructor)]
+        pub fn custom_era(
+            share_token_hash: Hash,
+            registry_code_hash: Hash,
+            nomination_agent_hash: Hash,
+            era: u64,
+        ) -> Self {
+            let caller = Self::env().caller();
+            let now = Self::env().block_timestamp();
+
+            let registry_ref = RegistryRef::new(caller, caller, caller, nomination_agent_hash)
+                .endowment(0)
+                .code_hash(registry_code_hash)
+                .salt_bytes(
+                    &[9_u8.to_le_bytes().as_ref(), caller.as_ref()].concat()[..4],
+                )
+                .instantiate();
+            let share_token_ref = TokenRef::new(Some(String::from("sAZERO")), Some(String::from("SAZ")))
+                .endowment(0)
+                .code_hash(share_token_hash)
+                .salt_bytes(
+                    &[7_u8.to_le_bytes().as_ref(), caller.as_ref()].concat()[..4],
+                )
+                .instantiate();
+
+            Self {
+                data: VaultData::new(
+                    caller,
+                    TokenRef::to_account_id(&share_token_ref),
+                    registry_ref,
+                    now,
+                    era,
+                ),
+            }
+        }
+
+        /// Allow users to convert AZERO into sAZERO
+        /// Mints the caller sAZERO based on the redemption ratio
+        ///
+        /// Minimum AZERO amount is required to stake
+        /// AZERO must be transferred via transferred_value
+        #[ink(message, payable)]
+        pub fn stake(&mut self) -> Result<Balance, VaultError> {
+            self.check_version()?;
+
+            self.stake_for(Self::env().caller())
+        }
+
+        /// Allow users to convert AZERO into sAZERO on behalf of another account
+        /// Mints the beneficiary sAZERO based on the redemption ratio
+        /// Retains a portion of the AZERO in the liquidity buffer when it is below its target size
+        ///
+        /// Minimum AZERO amount is required to stake
+        /// AZERO must be transferred via transferred_value
+        /// Beneficiary must be allowlisted while the allowlist is enabled
+        #[ink(message, payable)]
+        pub fn stake_for(&mut self, beneficiary: AccountId) -> Result<Balance, VaultError> {
+            self.check_version()?;
+
+            if self.data.allowlist_enabled && !self.is_allowlisted(beneficiary) {
+                return Err(VaultError::NotAllowlisted);
+            }
+
+            self.deposit(beneficiary)
+        }
+
+        /// Allow allowlisted users to convert AZERO into sAZERO proving membership of the allowlist Merkle root
+        /// Mints the caller sAZERO based on the redemption ratio
+        ///
+        /// `proof` - Sibling hashes from the caller's leaf to `allowlist_merkle_root`
+        #[ink(message, payable)]
+        pub fn stake_with_proof(&mut self, proof: Vec<[u8; 32]>) -> Result<Balance, VaultError> {
+            self.check_version()?;
+
+            let caller = Self::env().caller();
+
+            if self.data.allowlist_enabled && !self.is_allowlisted_with_proof(caller, proof) {
+                return Err(VaultError::NotAllowlisted);
+            }
+
+            self.deposit(caller)
+        }
+
+        #[ink(message, payable)]
+        pub fn stake_with_referral(&mut self, referral_id: AccountId) -> Result<Balance, VaultError> {
+            self.check_version()?;
+
+            self.stake_for_with_referral(Self::env().caller(), referral_id)
+        }
+
+        #[ink(message, payable)]
+        pub fn stake_for_with_referral(
+            &mut self,
+            beneficiary: AccountId,
+            referral_id: AccountId,
+        ) -> Result<Balance, VaultError> {
+            self.check_version()?;
+
+            if referral_id == beneficiary {
+                return Err(VaultError::InvalidReferral);
+            }
+            if self.data.referral_whitelist_enabled && !self.data.referral_whitelist.contains(referral_id) {
+                return Err(VaultError::InvalidReferral);
+            }
+
+            let new_shares = self.stake_for(beneficiary)?;
+
+            // Fees were updated while staking
+            self.data.add_referred_shares(beneficiary, referral_id, new_shares, Self::env().transferred_value());
+
+            Self::emit_event(
+                Self::env(),
+                Event::Referral(Referral {
+                    referral_id,
+                    staker: Self::env().caller(),
+                    beneficiary,
+                    azero: Self::env().transferred_value(),
+                }),
+            );
+            Ok(new_shares)
+        }
+
+        /// Alternative staking method with slippage protection
+        ///
+        /// Fails when the block timestamp is past `deadline`
+        /// Fails when fewer than `min_shares` sAZERO would be minted
+        #[ink(message, payable)]
+        pub fn stake_with_min_shares(
+            &mut self,
+            min_shares: Balance,
+            deadline: Timestamp,
+        ) -> Result<Balance, VaultError> {
+            self.check_version()?;
+
+            if Self::env().block_timestamp() > deadline {
+                return Err(VaultError::DeadlineExpired);
+            }
+
+            let new_shares = self.stake()?;
+
+            if new_shares < min_shares {
+                return Err(VaultError::MinimumShares);
+            }
+
+            Ok(new_shares)
+        }
+
+        /// Allow user to begin the unlock process
+        /// Transfers sAZERO specified in `shares` argument to the vault contract
+        /// Unlock is batched into current two era batch request
+        ///
+        /// Caller must approve the psp22 token contract beforehand
+        /// Returns the unlock id
+        #[ink(message)]
+        pub fn request_unlock(&mut self, shares: Balance) -> Result<u128, VaultError> {
+            self.check_version()?;
+
+            if self.data.paused.unlock_requests {
+                return Err(VaultError::Paused);
+            }
+
+            let caller = Self::env().caller();
+
+            self.auto_dispatch_batches();
+
+            self.transfer_shares_from(&caller, &Self::env().account_id(), shares)?;
+
+            self.create_unlock_request(caller, shares)
+        }
+
+        /// Allow an approved spender to begin the unlock process on behalf of `owner`
+        /// Transfers sAZERO specified in `shares` argument from `owner` to the vault contract
+        /// Unlock request belongs to `owner` who receives the redeemed AZERO
+        ///
+        /// Owner must approve the caller on the psp22 token contract beforehand
+        /// Consumes the caller's allowance
+        /// Share tokens deployed before `spender_transfer_from` was added do not support this message
+        /// Returns the unlock id
+        #[ink(message)]
+        pub fn request_unlock_for(&mut self, owner: AccountId, shares: Balance) -> Result<u128, VaultError> {
+            self.check_version()?;
+
+            if self.data.paused.unlock_requests {
+                return Err(VaultError::Paused);
+            }
+
+            let caller = Self::env().caller();
+
+            self.auto_dispatch_batches();
+
+            self.spender_transfer_shares_from(&caller, &owner, &Self::env().account_id(), shares)?;
+
+            self.create_unlock_request(owner, shares)
+        }
+
+        /// Alternative unlock request method with slippage protection
+        ///
+        /// Fails when the block timestamp is past `deadline`
+        /// Fails when the spot value of `shares` is less than `min_azero`
+        /// AZERO ultimately redeemed is determined when the batch unlock request is sent
+        #[ink(message)]
+        pub fn request_unlock_with_min_azero(
+            &mut self,
+            shares: Balance,
+            min_azero: Balance,
+            deadline: Timestamp,
+        ) -> Result<u128, VaultError> {
+            self.check_version()?;
+
+            let now = Self::env().block_timestamp();
+
+            if now > deadline {
+                return Err(VaultError::DeadlineExpired);
+            }
+
+            // Update fees before calculating redemption ratio
+            self.data.update_fees(now);
+
+            if self.get_azero_from_shares(shares) < min_azero {
+                return Err(VaultError::MinimumAzero);
+            }
+
+            self.request_unlock(shares)
+        }
+
+        /// Allow user to instantly convert sAZERO into AZERO from the liquidity buffer
+        /// Transfers sAZERO specified in `shares` argument to the vault contract and burns it
+        /// The instant unlock fee remains in the liquidity buffer, accruing to remaining stakers
+        ///
+        /// Caller must approve the psp22 token contract beforehand
+        /// AZERO received must be at least `min_azero_out`
+        /// AZERO received cannot exceed `max_instant_unlock` or the liquidity buffer
+        #[ink(message)]
+        pub fn instant_unlock(&mut self, shares: Balance, min_azero_out: Balance) -> Result<Balance, VaultError> {
+            self.check_version()?;
+
+            if self.data.paused.unlock_requests {
+                return Err(VaultError::Paused);
+            }
+
+            let caller = Self::env().caller();
+
+            // Update fees before calculating redemption ratio and burning shares
+            self.data.update_fees(Self::env().block_timestamp());
+            self.data.remove_referred_shares(caller, shares);
+
+            let azero_value = self.get_azero_from_shares(shares);
+            self.data.sub_net_staked(caller, azero_value);
+            let fee = self.data.pro_rata(
+                azero_value,
+                self.data.instant_unlock_fee_percentage as u128,
+                BIPS as u128,
+            );
+            let azero = azero_value - fee;
+
+            if azero < min_azero_out {
+                return Err(VaultError::MinimumAzero);
+            }
+            if azero > self.data.max_instant_unlock {
+                return Err(VaultError::InstantUnlockLimit);
+            }
+            if azero > self.data.liquidity_buffer {
+                return Err(VaultError::InsufficientLiquidity);
+            }
+
+            // Handle sAZERO
+            self.transfer_shares_from(&caller, &Self::env().account_id(), shares)?;
+            self.burn_shares(shares)?;
+
+            // Handle AZERO
+            self.data.liquidity_buffer -= azero;
+            Self::env().transfer(caller, azero)?;
+
+            Self::emit_event(
+                Self::env(),
+                Event::InstantUnlocked(InstantUnlocked {
+                    staker: caller,
+                    shares,
+                    azero,
+                    fee,
+                }),
+            );
+
+            self.record_rate_checkpoint();
+
+            Ok(azero)
+        }
+
+        /// Allow user to cancel their unlock request
+        ///
+        /// Associated batch unlock request must not have been sent
+        #[ink(message)]
+        pub fn cancel_unlock_request(&mut self, unlock_id: u128) -> Result<(), VaultError> {
+            self.check_version()?;
+
+            let caller = Self::env().caller();
+
+            let unlock_request = self.data.get_user_unlock_request(caller, unlock_id)?;
+
+            self.cancel_unlock_shares(caller, unlock_id, unlock_request, None)
+        }
+
+        /// Allow user to cancel part of their unlock request
+        ///
+        /// Cancelling all of the requested shares deletes the unlock request
+        /// Associated batch unlock request must not have been sent
+        #[ink(message)]
+        pub fn cancel_unlock_request_partial(&mut self, unlock_id: u128, shares: Balance) -> Result<(), VaultError> {
+            self.check_version()?;
+
+            let caller = Self::env().caller();
+
+            let unlock_request = self.data.get_user_unlock_request(caller, unlock_id)?;
+
+            self.cancel_unlock_shares(caller, unlock_id, unlock_request, Some(shares))
+        }
+
+        /// Allow user to approve an operator to transfer one of their unlock requests
+        ///
+        /// Specifying `None` revokes the existing approval
+        /// Approval is cleared when the unlock request is transferred
+        #[ink(message)]
+        pub fn approve_unlock_request(
+            &mut self,
+            unlock_id: u128,
+            operator: Option<AccountId>,
+        ) -> Result<(), VaultError> {
+            self.check_version()?;
+
+            let caller = Self::env().caller();
+
+            let mut unlock_request = self.data.get_user_unlock_request(caller, unlock_id)?;
+
+            if unlock_request.operator == operator {
+                return Err(VaultError::NoChange);
+            }
+
+            unlock_request.operator = operator;
+            self.data.unlock_requests.insert(unlock_id, &unlock_request);
+
+            Self::emit_event(
+                Self::env(),
+                Event::UnlockRequestApproved(UnlockRequestApproved {
+                    staker: caller,
+                    operator,
+                    unlock_id,
+                }),
+            );
+
+            Ok(())
+        }
+
+        /// Allow user to transfer one of their unlock requests to another account
+        ///
+        /// Recipient becomes entitled to the redeemed AZERO or the shares upon cancellation
+        /// Unlock id is preserved
+        #[ink(message)]
+        pub fn transfer_unlock_request(&mut self, to: AccountId, unlock_id: u128) -> Result<(), VaultError> {
+            self.check_version()?;
+
+            let caller = Self::env().caller();
+
+            self.move_unlock_request(caller, to, unlock_id)
+        }
+
+        /// Allow an approved operator to transfer an unlock request on behalf of its owner
+        ///
+        /// Caller must be the approved operator for the unlock request
+        /// Unlock id is preserved
+        #[ink(message)]
+        pub fn transfer_unlock_request_from(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            unlock_id: u128,
+        ) -> Result<(), VaultError> {
+            self.check_version()?;
+
+            let caller = Self::env().caller();
+
+            let unlock_request = self.data.get_user_unlock_request(from, unlock_id)?;
+
+            if unlock_request.operator != Some(caller) {
+                return Err(VaultError::InvalidPermissions);
+            }
+
+            self.move_unlock_request(from, to, unlock_id)
+        }
+
+        /// Trigger unlock requests of previous batched requests
+        /// Distributes unlock requests to nominators according to current stake imbalances
+        /// Calculates a batch spot values for sAZERO in the batches
+        /// Burns associated sAZERO
+        /// Unbonds additional AZERO to replenish the liquidity buffer when it is below its target size
+        ///
+        /// Cannot be called for a batch that has not concluded
+        /// Cannot be called for a batch that has already been redeemed
+        /// Batch IDs must be specified in ascending order (for gas efficient duplicate check)
+        #[ink(message)]
+        pub fn send_batch_unlock_requests(&mut self, batch_ids: Vec<u64>) -> Result<(), VaultError> {
+            self.check_version()?;
+
+            let caller = Self::env().caller();
+
+            let processed = self.send_batches(batch_ids, Self::env().block_timestamp())?;
+            self.pay_keeper_reward(caller, processed)?;
+
+            Ok(())
+        }
+
+        /// Trigger unlock requests of all concluded batches which have not been sent
+        /// Determines the batch ids to send starting from the oldest unsent batch
+        ///
+        /// Can be called by anyone
+        /// Returns the number of batches sent
+        #[ink(message)]
+        pub fn send_pending_batches(&mut self) -> Result<u32, VaultError> {
+            self.check_version()?;
+
+            let caller = Self::env().caller();
+
+            let (count, processed) = self.dispatch_pending_batches(None)?;
+            self.pay_keeper_reward(caller, processed)?;
+
+            Ok(count)
+        }
+
+
+        /// Attempts to claim unbonded AZERO from all validators
+        /// Replenishes the liquidity buffer with AZERO unbonded for it
+        ///
+        /// Can be called by anyone
+        /// Caller receives a keeper reward based on the AZERO withdrawn
+        #[ink(message)]
+        pub fn delegate_withdraw_unbonded(&mut self) -> Result<(), VaultError> {
+            self.check_version()?;
+
+            let caller = Self::env().caller();
+
+            let withdrawn = self.withdraw_unbonded()?;
+            self.pay_keeper_reward(caller, withdrawn)?;
+
+            Ok(())
+        }
+
+        /// Allows a user to withdraw staked AZERO
+        ///
+        /// Returns original deposit amount plus interest to depositor address
+        /// Queries the redeemable amount by user AccountId and unlock id
+        /// Associated batch unlock request must have been completed
+        /// Deletes the user's unlock request
+        /// Burns the associated sAZERO tokens
+        #[ink(message)]
+        pub fn redeem(&mut self, user: AccountId, unlock_id: u128) -> Result<(), VaultError> {
+            self.check_version()?;
+
+            if self.data.paused.redemption {
+                return Err(VaultError::Paused);
+            }
+
+            let now = Self::env().block_timestamp();
+
+            // Ensure user specified a valid unlock id
+            let unlock_request = self.data.get_user_unlock_request(user, unlock_id)?;
+
+            // Ensure batch unlock has been redeemed and completed
+            let azero = self.data.get_redeemable_azero(&unlock_request, now)?;
+
+            // Delete completed user unlock request
+            self.data.remove_unlock_request(unlock_id);
+
+            // Send AZERO to user
+            Self::env().transfer(user, azero)?;
+
+            Self::emit_event(
+                Self::env(),
+                Event::UnlockRedeemed(UnlockRedeemed {
+                    staker: user,
+                    azero,
+                    unlock_id,
+                    batch_id: unlock_request.batch_id,
+                }),
+            );
+
+            Ok(())
+        }
+
+        /// Alternative method for a user to withdraw staked AZERO
+        ///
+        /// This should be called instead of `redeem()` when insufficient AZERO exists in the Vault and
+        /// validator(s) have unbonded AZERO which can be claimed
+        #[ink(message)]
+        pub fn redeem_with_withdraw(&mut self, user: AccountId, unlock_id: u128) -> Result<(), VaultError> {
+            self.check_version()?;
+
+            // Claim all unbonded AZERO into Vault
+            self.withdraw_unbonded()?;
+
+            self.redeem(user, unlock_id)?;
+
+            Ok(())
+        }
+
+        /// Allows a user to withdraw staked AZERO from several unlock requests at once
+        ///
+        /// Pays out every specified unlock request whose batch unlock request has completed
+        /// Skips unlock requests which are not yet redeemable
+        /// Deletes the redeemed unlock requests
+        /// Returns the total AZERO sent to the user
+        #[ink(message)]
+        pub fn redeem_many(&mut self, user: AccountId, unlock_ids: Vec<u128>) -> Result<Balance, VaultError> {
+            self.check_version()?;
+
+            if self.data.paused.redemption {
+                return Err(VaultError::Paused);
+            }
+
+            let now = Self::env().block_timestamp();
+
+            let mut total_azero: Balance = 0;
+            let mut redeemed: u128 = 0;
+
+            for unlock_id in unlock_ids.into_iter() {
+                // Ensure user specified a valid unlock id
+                let unlock_request = self.data.get_user_unlock_request(user, unlock_id)?;
+
+                // Skip unlock requests which are not redeemable yet
+                let azero = match self.data.get_redeemable_azero(&unlock_request, now) {
+                    Ok(azero) => azero,
+                    Err(VaultError::InvalidBatchUnlockRequest) | Err(VaultError::CooldownPeriod) => continue,
+                    Err(e) => return Err(e),
+                };
+
+                // Delete completed user unlock request
+                self.data.remove_unlock_request(unlock_id);
+
+                total_azero += azero;
+                redeemed += 1;
+
+                Self::emit_event(
+                    Self::env(),
+                    Event::UnlockRedeemed(UnlockRedeemed {
+                        staker: user,
+                        azero,
+                        unlock_id,
+                        batch_id: unlock_request.batch_id,
+                    }),
+                );
+            }
+
+            // Send AZERO to user
+            if total_azero > 0 {
+                Self::env().transfer(user, total_azero)?;
+            }
+
+            Self::emit_event(
+                Self::env(),
+                Event::UnlocksRedeemed(UnlocksRedeemed {
+                    staker: user,
+                    azero: total_azero,
+                    count: redeemed,
+                }),
+            );
+
+            Ok(total_azero)
+        }
+
+        /// Allows a user to withdraw staked AZERO from all of their redeemable unlock requests
+        ///
+        /// See `redeem_many()`
+        #[ink(message)]
+        pub fn redeem_all(&mut self, user: AccountId) -> Result<Balance, VaultError> {
+            self.check_version()?;
+
+            let unlock_ids = self.data.user_unlock_requests.get(user).unwrap_or_default();
+
+            self.redeem_many(user, unlock_ids)
+        }
+
+        /// Alternative method for a user to withdraw staked AZERO from several unlock requests at once
+        ///
+        /// This should be called instead of `redeem_many()` when insufficient AZERO exists in the Vault and
+        /// validator(s) have unbonded AZERO which can be claimed
+        #[ink(message)]
+        pub fn redeem_many_with_withdraw(&mut self, user: AccountId, unlock_ids: Vec<u128>) -> Result<Balance, VaultError> {
+            self.check_version()?;
+
+            // Claim all unbonded AZERO into Vault
+            self.withdraw_unbonded()?;
+
+            self.redeem_many(user, unlock_ids)
+        }
+
+        /// Alternative method for a user to withdraw staked AZERO from all of their redeemable unlock requests
+        ///
+        /// This should be called instead of `redeem_all()` when insufficient AZERO exists in the Vault and
+        /// validator(s) have unbonded AZERO which can be claimed
+        #[ink(message)]
+        pub fn redeem_all_with_withdraw(&mut self, user: AccountId) -> Result<Balance, VaultError> {
+            self.check_version()?;
+
+            // Claim all unbonded AZERO into Vault
+            self.withdraw_unbonded()?;
+
+            self.redeem_all(user)
+        }
+
+        /// Compound earned interest for all validators
+        ///
+        /// Can be called by anyone
+        /// Caller receives an AZERO incentive based on the total AZERO amount compounded
+        /// Performance fee is accrued as virtual shares based on the total AZERO amount compounded
+        /// Pauses staking, unlock requests, batch sending, and redemption when the agents' pool balances
+        /// fall short of the bonded AZERO by more than `GUARDIAN_LOSS_TOLERANCE`
+        #[ink(message)]
+        pub fn compound(&mut self) -> Result<Balance, VaultError> {
+            self.check_version()?;
+
+            if self.data.paused.compounding {
+                return Err(VaultError::Paused);
+            }
+
+            let caller = Self::env().caller();
+            let now = Self::env().block_timestamp();
+
+            // Delegate compounding to all nominator pools
+            let (compounded, incentive, total_loss) = self.data.delegate_compound(now)?;
+
+            // Guardian pause upon a decrease in staked value
+            if total_loss > 0 {
+                self.guardian_pause(total_loss);
+            }
+
+            let (performance_fee, _) = self.data.charge_performance_fee(compounded, now);
+
+            // Portion of the incentive is retained by the insurance fund
+            let insurance = self.data.pro_rata(
+                incentive,
+                self.data.insurance_incentive_percentage as u128,
+                BIPS as u128,
+            );
+            let incentive = incentive - insurance;
+            if insurance > 0 {
+                self.data.insurance_azero += insurance;
+
+                Self::emit_event(
+                    Self::env(),
+                    Event::InsuranceToppedUp(InsuranceToppedUp {
+                        shares: 0,
+                        azero: insurance,
+                    }),
+                );
+            }
+
+            // Send AZERO incentive to caller
+            if incentive > 0 {
+                Self::env().transfer(caller, incentive)?;
+            }
+
+            self.auto_dispatch_batches();
+
+            Self::emit_event(
+                Self::env(),
+                Event::Compounded(Compounded {
+                    caller,
+                    azero: compounded,
+                    incentive,
+                    performance_fee,
+                    virtual_shares: self.get_current_virtual_shares(),
+                }),
+            );
+
+            self.record_rate_checkpoint();
+
+            Ok(incentive)
+        }
+
+        /// Claim referral rewards accrued by the caller's referral id
+        /// Mints the rewards as sAZERO from the virtual shares reserved for referrals
+        ///
+        /// Only sAZERO minted through a referred stake is attributed to a referrer
+        /// Attribution does not follow sAZERO moved by plain transfers
+        ///
+        /// Returns the sAZERO minted
+        #[ink(message)]
+        pub fn claim_referral_rewards(&mut self) -> Result<Balance, VaultError> {
+            self.check_version()?;
+
+            let caller = Self::env().caller();
+
+            self.data.update_fees(Self::env().block_timestamp());
+
+            let shares = self.data.take_referral_rewards(caller);
+            if shares > 0 {
+                self.mint_shares(shares, caller)?;
+            }
+
+            Self::emit_event(
+                Self::env(),
+                Event::ReferralRewardsClaimed(ReferralRewardsClaimed {
+                    referral_id: caller,
+                    shares,
+                }),
+            );
+
+            self.record_rate_checkpoint();
+
+            Ok(shares)
+        }
+
+        /// Detects slashes by comparing the pool balance of each agent against the expected amount
+        ///
+        /// Losses are socialized across all sAZERO holders, including unsent unlock batches, through the redemption ratio
+        /// Pauses staking, unlock requests, batch sending, and redemption when the loss exceeds `GUARDIAN_LOSS_TOLERANCE`
+        /// Can be called by anyone
+        #[ink(message)]
+        pub fn sync_pool_values(&mut self) -> Result<Balance, VaultError> {
+            self.check_version()?;
+
+            let now = Self::env().block_timestamp();
+
+            self.data.update_fees(now);
+
+            let total_pooled_ = self.data.total_pooled; // shadow
+            let (total_loss, total_recovered, agents) = self.data.sync_pool_values();
+
+            if total_loss > 0 || total_recovered > 0 {
+                Self::emit_event(
+                    Self::env(),
+                    Event::SlashDetected(SlashDetected {
+                        total_loss,
+                        total_recovered,
+                        agents,
+                    }),
+                );
+
+                self.record_rate_checkpoint();
+            }
+
+            // Guardian pause upon a loss beyond the tolerance
+            if self.data.exceeds_loss_tolerance(total_loss, total_pooled_) {
+                self.guardian_pause(total_loss);
+            }
+
+            Ok(total_loss)
+        }
+
+        /// Allow anyone to increase the redemption ratio by donating AZERO
+        /// Bonds the AZERO to the nominator pools without minting sAZERO
+        ///
+        /// Donated AZERO is released into the redemption ratio linearly over `donation_duration`
+        /// AZERO must be transferred via transferred_value
+        /// Requires existing sAZERO so that the first staker cannot be front-run by a donation
+        #[ink(message, payable)]
+        pub fn donate(&mut self) -> Result<(), VaultError> {
+            self.check_version()?;
+
+            if self.data.paused.staking {
+                return Err(VaultError::Paused);
+            }
+            if self.get_total_shares() == 0 {
+                return Err(VaultError::ZeroShares);
+            }
+
+            let caller = Self::env().caller();
+            let now = Self::env().block_timestamp();
+            let azero = Self::env().transferred_value();
+
+            self.data.update_fees(now);
+
+            self.data.delegate_bonding(azero)?;
+            self.data.add_donation(azero, now);
+
+            Self::emit_event(
+                Self::env(),
+                Event::Donated(Donated {
+                    donor: caller,
+                    azero,
+                    release_end: if self.data.donation_duration == 0 { now } else { self.data.donation_stream.end },
+                }),
+            );
+
+            self.record_rate_checkpoint();
+
+            Ok(())
+        }
+
+        /// =========================== Restricted Functions: Owner Role ===========================
+
+        /// Claim fees by inflating sAZERO supply
+        ///
+        /// Caller must have the owner role (`RoleType::Owner`)
+        /// Mints a portion of virtual shares as sAZERO to the Vault for the insurance fund
+        /// Mints the remaining virtual shares as sAZERO to the fee recipients by weight, or to the owner if none are set
+        /// Effectively serves as a compounding for protocol fee
+        /// sets total_shares_virtual to the shares reserved for referral rewards
+        #[ink(message)]
+        pub fn withdraw_fees(&mut self) -> Result<(), VaultError> {
+            self.check_version()?;
+
+            let caller = Self::env().caller();
+            let now = Self::env().block_timestamp();
+
+            if caller != self.data.get_role(RoleType::Owner) {
+                return Err(VaultError::InvalidPermissions);
+            }
+
+            self.data.update_fees(now);
+
+            // Virtual shares reserved for referral rewards remain until claimed
+            let shares = self.data.total_shares_virtual - self.data.referral_shares_reserved;
+            self.data.total_shares_virtual = self.data.referral_shares_reserved;
+
+            let insurance = self.data.pro_rata(shares, self.data.insurance_fee_percentage as u128, BIPS as u128);
+            if insurance > 0 {
+                self.mint_shares(insurance, Self::env().account_id())?;
+                self.data.insurance_shares += insurance;
+
+                Self::emit_event(
+                    Self::env(),
+                    Event::InsuranceToppedUp(InsuranceToppedUp {
+                        shares: insurance,
+                        azero: 0,
+                    }),
+                );
+            }
+
+            for (recipient, portion) in self.data.split_fees(shares - insurance) {
+                if portion == 0 {
+                    continue;
+                }
+
+                self.mint_shares(portion, recipient)?;
+
+                Self::emit_event(
+                    Self::env(),
+                    Event::FeesWithdrawn(FeesWithdrawn {
+                        recipient,
+                        shares: portion,
+                    }),
+                );
+            }
+
+            self.record_rate_checkpoint();
+
+            Ok(())
+        }
+
+        /// Update the minimum stake amount
+        ///
+        /// Caller must have the owner role (`RoleType::Owner`)
+        #[ink(message)]
+        pub fn adjust_minimum_stake(&mut self, new_minimum_stake: Balance) -> Result<(), VaultError> {
+            self.check_version()?;
+
+            let caller = Self::env().caller();
+
+            if caller != self.data.get_role(RoleType::Owner) {
+                return Err(VaultError::InvalidPermissions);
+            }
+            if self.data.minimum_stake == new_minimum_stake {
+                return Err(VaultError::NoChange);
+            }
+
+            self.data.minimum_stake = new_minimum_stake;
+
+            Self::emit_event(
+                Self::env(),
+                Event::MinimumStakeAdjusted(MinimumStakeAdjusted {
+                    new_minimum_stake,
+                }),
+            );
+
+            Ok(())
+        }
+
+        /// Update the maximum bonded AZERO
+        ///
+        /// Caller must have the owner role (`RoleType::Owner`)
+        /// Does not affect AZERO which is already staked
+        #[ink(message)]
+        pub fn adjust_max_total_pooled(&mut self, new_max_total_pooled: Balance) -> Result<(), VaultError> {
+            self.check_version()?;
+
+            let caller = Self::env().caller();
+
+            if caller != self.data.get_role(RoleType::Owner) {
+                return Err(VaultError::InvalidPermissions);
+            }
+            if self.data.max_total_pooled == new_max_total_pooled {
+                return Err(VaultError::NoChange);
+            }
+
+            self.data.max_total_pooled = new_max_total_pooled;
+
+            Self::emit_event(
+                Self::env(),
+                Event::MaxTotalPooledAdjusted(MaxTotalPooledAdjusted {
+                    new_max_total_pooled,
+                }),
+            );
+
+            Ok(())
+        }
+
+        /// Update the maximum net AZERO staked per account
+        ///
+        /// Caller must have the owner role (`RoleType::Owner`)
+        /// None removes the per account maximum
+        #[ink(message)]
+        pub fn adjust_max_account_stake(&mut self, new_max_account_stake: Option<Balance>) -> Result<(), VaultError> {
+            self.check_version()?;
+
+            let caller = Self::env().caller();
+
+            if caller != self.data.get_role(RoleType::Owner) {
+                return Err(VaultError::InvalidPermissions);
+            }
+            if self.data.max_account_stake == new_max_account_stake {
+                return Err(VaultError::NoChange);
+            }
+
+            self.data.max_account_stake = new_max_account_stake;
+
+            Self::emit_event(
+                Self::env(),
+                Event::MaxAccountStakeAdjusted(MaxAccountStakeAdjusted {
+                    new_max_account_stake,
+                }),
+            );
+
+            Ok(())
+        }
+
+        /// Update the target size of the liquidity buffer
+        ///
+        /// Caller must have the owner role (`RoleType::Owner`)
+        /// Also sets the portion of each stake retained in the liquidity buffer until the target is reached
+        #[ink(message)]
+        pub fn adjust_buffer_target(&mut self, new_buffer_target: u16) -> Result<(), VaultError> {
+            self.check_version()?;
+
+            let caller = Self::env().caller();
+
+            if caller != self.data.get_role(RoleType::Owner) {
+                return Err(VaultError::InvalidPermissions);
+            }
+            if self.data.buffer_target_percentage == new_buffer_target {
+                return Err(VaultError::NoChange);
+            }
+            if new_buffer_target >= BIPS {
+                return Err(VaultError::InvalidPercent);
+            }
+
+            self.data.buffer_target_percentage = new_buffer_target;
+
+            Self::emit_event(
+                Self::env(),
+                Event::BufferTargetAdjusted(BufferTargetAdjusted {
+                    new_buffer_target,
+                }),
+            );
+
+            Ok(())
+        }
+
+        /// Update the maximum amount of AZERO which can be instantly unlocked per call
+        ///
+        /// Caller must have the owner role (`RoleType::Owner`)
+        #[ink(message)]
+        pub fn adjust_max_instant_unlock(&mut self, new_max_instant_unlock: Balance) -> Result<(), VaultError> {
+            self.check_version()?;
+
+            let caller = Self::env().caller();
+
+            if caller != self.data.get_role(RoleType::Owner) {
+                return Err(VaultError::InvalidPermissions);
+            }
+            if self.data.max_instant_unlock == new_max_instant_unlock {
+                return Err(VaultError::NoChange);
+            }
+
+            self.data.max_instant_unlock = new_max_instant_unlock;
+
+            Self::emit_event(
+                Self::env(),
+                Event::MaxInstantUnlockAdjusted(MaxInstantUnlockAdjusted {
+                    new_max_instant_unlock,
+                }),
+            );
+
+            Ok(())
+        }
+
+        /// Update the timelock and rate limits of fee changes
+        ///
+        /// Caller must have the owner role (`RoleType::Owner`)
+        /// Tightening limits applies immediately, loosening any limit is queued for the current `fee_change_delay`
+        /// Replaces a previously queued change of the limits
+        /// Does not affect fee changes which are already queued
+        #[ink(message)]
+        pub fn adjust_fee_change_limits(
+            &mut self,
+            new_delay: u64,
+            new_period: u64,
+            new_max_change: u16,
+        ) -> Result<(), VaultError> {
+            self.check_version()?;
+
+            let caller = Self::env().caller();
+            let now = Self::env().block_timestamp();
+
+            if caller != self.data.get_role(RoleType::Owner) {
+                return Err(VaultError::InvalidPermissions);
+            }
+            if self.data.fee_change_delay == new_delay
+                && self.data.fee_change_period == new_period
+                && self.data.max_fee_change == new_max_change
+            {
+                return Err(VaultError::NoChange);
+            }
+            if new_max_change > BIPS {
+                return Err(VaultError::InvalidPercent);
+            }
+
+            let loosens = new_delay < self.data.fee_change_delay
+                || new_period < self.data.fee_change_period
+                || new_max_change > self.data.max_fee_change;
+            let effective_time = if loosens { now + self.data.fee_change_delay } else { now };
+
+            self.data.fee_change_limits_proposal = Some(FeeChangeLimitsProposal {
+                new_delay,
+                new_period,
+                new_max_change,
+                effective_time,
+            });
+
+            Self::emit_event(
+                Self::env(),
+                Event::FeeChangeLimitsQueued(FeeChangeLimitsQueued {
+                    new_delay,
+                    new_period,
+                    new_max_change,
+                    effective_time,
+                }),
+            );
+
+            if effective_time == now {
+                self.apply_queued_fee_change_limits()?;
+            }
+
+            Ok(())
+        }
+
+        /// Apply a queued change of the fee change limits once its effective time has passed
+        ///
+        /// Can be called by anyone
+        #[ink(message)]
+        pub fn apply_fee_change_limits(&mut self) -> Result<(), VaultError> {
+            self.check_version()?;
+
+            self.apply_queued_fee_change_limits()
+        }
+
+        /// Toggle whether only registered referral ids are valid
+        ///
+        /// Caller must have the owner role (`RoleType::Owner`)
+        #[ink(message)]
+        pub fn set_referral_whitelist_enabled(&mut self, enabled: bool) -> Result<(), VaultError> {
+            self.check_version()?;
+
+            let caller = Self::env().caller();
+
+            if caller != self.data.get_role(RoleType::Owner) {
+                return Err(VaultError::InvalidPermissions);
+            }
+            if self.data.referral_whitelist_enabled == enabled {
+                return Err(VaultError::NoChange);
+            }
+
+            self.data.referral_whitelist_enabled = enabled;
+
+            Self::emit_event(
+                Self::env(),
+                Event::ReferralWhitelistToggled(ReferralWhitelistToggled {
+                    enabled,
+                }),
+            );
+
+            Ok(())
+        }
+
+        /// Register referral ids which are valid while the referral whitelist is enabled
+        ///
+        /// Caller must have the owner role (`RoleType::Owner`)
+        #[ink(message)]
+        pub fn register_referrers(&mut self, referral_ids: Vec<AccountId>) -> Result<(), VaultError> {
+            self.check_version()?;
+
+            let caller = Self::env().caller();
+
+            if caller != self.data.get_role(RoleType::Owner) {
+                return Err(VaultError::InvalidPermissions);
+            }
+
+            for referral_id in referral_ids {
+                if self.data.referral_whitelist.contains(referral_id) {
+                    continue;
+                }
+
+                self.data.referral_whitelist.insert(referral_id, &());
+
+                Self::emit_event(
+                    Self::env(),
+                    Event::ReferrerRegistered(ReferrerRegistered {
+                        referral_id,
+                    }),
+                );
+            }
+
+            Ok(())
+        }
+
+        /// Unregister referral ids
+        /// Rewards already accrued by an unregistered referral id remain claimable
+        ///
+        /// Caller must have the owner role (`RoleType::Owner`)
+        #[ink(message)]
+        pub fn unregister_referrers(&mut self, referral_ids: Vec<AccountId>) -> Result<(), VaultError> {
+            self.check_version()?;
+
+            let caller = Self::env().caller();
+
+            if caller != self.data.get_role(RoleType::Owner) {
+                return Err(VaultError::InvalidPermissions);
+            }
+
+            for referral_id in referral_ids {
+                if !self.data.referral_whitelist.contains(referral_id) {
+                    continue;
+                }
+
+                self.data.referral_whitelist.remove(referral_id);
+
+                Self::emit_event(
+                    Self::env(),
+                    Event::ReferrerUnregistered(ReferrerUnregistered {
+                        referral_id,
+                    }),
+                );
+            }
+
+            Ok(())
+        }
+
+        /// Update the maximum number of batch unlock requests lazily sent per user interaction
+        ///
+        /// Caller must have the owner role (`RoleType::Owner`)
+        /// Zero disables lazy sending of batch unlock requests
+        #[ink(message)]
+        pub fn adjust_max_batches_per_dispatch(&mut self, new_max_batches_per_dispatch: u32) -> Result<(), VaultError> {
+            self.check_version()?;
+
+            let caller = Self::env().caller();
+
+            if caller != self.data.get_role(RoleType::Owner) {
+                return Err(VaultError::InvalidPermissions);
+            }
+            if self.data.max_batches_per_dispatch == new_max_batches_per_dispatch {
+                return Err(VaultError::NoChange);
+            }
+
+            self.data.max_batches_per_dispatch = new_max_batches_per_dispatch;
+
+            Self::emit_event(
+                Self::env(),
+                Event::MaxBatchesPerDispatchAdjusted(MaxBatchesPerDispatchAdjusted {
+                    new_max_batches_per_dispatch,
+                }),
+            );
+
+            Ok(())
+        }
+
+        /// Update the minimum time between rate checkpoints
+        ///
+        /// Caller must have the owner role (`RoleType::Owner`)
+        #[ink(message)]
+        pub fn adjust_checkpoint_granularity(&mut self, new_checkpoint_granularity: u64) -> Result<(), VaultError> {
+            self.check_version()?;
+
+            let caller = Self::env().caller();
+
+            if caller != self.data.get_role(RoleType::Owner) {
+                return Err(VaultError::InvalidPermissions);
+            }
+            if self.data.checkpoint_granularity == new_checkpoint_granularity {
+                return Err(VaultError::NoChange);
+            }
+
+            self.data.checkpoint_granularity = new_checkpoint_granularity;
+
+            Self::emit_event(
+                Self::env(),
+                Event::CheckpointGranularityAdjusted(CheckpointGranularityAdjusted {
+                    new_checkpoint_granularity,
+                }),
+            );
+
+            Ok(())
+        }
+
+        /// Report the AZERO lost by an agent's nomination pool which is not reflected in the agent's staked value
+        ///
+        /// Caller must have the owner role (`RoleType::Owner`)
+        /// Replaces the previous report so that a loss can be corrected
+        /// Takes effect on the redemption ratio once `sync_pool_values()` is called
+        #[ink(message)]
+        pub fn report_agent_loss(&mut self, agent: AccountId, azero: Balance) -> Result<(), VaultError> {
+            self.check_version()?;
+
+            let caller = Self::env().caller();
+
+            if caller != self.data.get_role(RoleType::Owner) {
+                return Err(VaultError::InvalidPermissions);
+            }
+            if self.data.agent_losses.get(agent).unwrap_or(0) == azero {
+                return Err(VaultError::NoChange);
+            }
+
+            if azero == 0 {
+                self.data.agent_losses.remove(agent);
+            } else {
+                self.data.agent_losses.insert(agent, &azero);
+            }
+
+            Self::emit_event(
+                Self::env(),
+                Event::AgentLossReported(AgentLossReported {
+                    agent,
+                    azero,
+                }),
+            );
+
+            Ok(())
+        }
+
+        /// Update the time over which donated AZERO is released into the redemption ratio
+        ///
+        /// Caller must have the owner role (`RoleType::Owner`)
+        /// Does not affect the release of donations which are already streaming
+        #[ink(message)]
+        pub fn adjust_donation_duration(&mut self, new_donation_duration: u64) -> Result<(), VaultError> {
+            self.check_version()?;
+
+            let caller = Self::env().caller();
+
+            if caller != self.data.get_role(RoleType::Owner) {
+                return Err(VaultError::InvalidPermissions);
+            }
+            if self.data.donation_duration == new_donation_duration {
+                return Err(VaultError::NoChange);
+            }
+
+            self.data.donation_duration = new_donation_duration;
+
+            Self::emit_event(
+                Self::env(),
+                Event::DonationDurationAdjusted(DonationDurationAdjusted {
+                    new_donation_duration,
+                }),
+            );
+
+            Ok(())
+        }
+
+        /// Update the time over which compounded AZERO vests into the redemption ratio
+        ///
+        /// Caller must have the owner role (`RoleType::Owner`)
+        /// Does not affect the vesting of rewards which are already vesting
+        #[ink(message)]
+        pub fn adjust_reward_vesting_period(&mut self, new_reward_vesting_period: u64) -> Result<(), VaultError> {
+            self.check_version()?;
+
+            let caller = Self::env().caller();
+
+            if caller != self.data.get_role(RoleType::Owner) {
+                return Err(VaultError::InvalidPermissions);
+            }
+            if self.data.reward_vesting_period == new_reward_vesting_period {
+                return Err(VaultError::NoChange);
+            }
+
+            self.data.reward_vesting_period = new_reward_vesting_period;
+
+            Self::emit_event(
+                Self::env(),
+                Event::RewardVestingPeriodAdjusted(RewardVestingPeriodAdjusted {
+                    new_reward_vesting_period,
+                }),
+            );
+
+            Ok(())
+        }
+
+        /// Update the portions of withdrawn fees and compounding incentives retained by the insurance fund
+        ///
+        /// Caller must have the owner role (`RoleType::Owner`)
+        #[ink(message)]
+        pub fn adjust_insurance_percentages(
+            &mut self,
+            new_fee_percentage: u16,
+            new_incentive_percentage: u16,
+        ) -> Result<(), VaultError> {
+            self.check_version()?;
+
+            let caller = Self::env().caller();
+
+            if caller != self.data.get_role(RoleType::Owner) {
+                return Err(VaultError::InvalidPermissions);
+            }
+            if self.data.insurance_fee_percentage == new_fee_percentage
+                && self.data.insurance_incentive_percentage == new_incentive_percentage
+            {
+                return Err(VaultError::NoChange);
+            }
+            if new_fee_percentage > BIPS || new_incentive_percentage > BIPS {
+                return Err(VaultError::InvalidPercent);
+            }
+
+            self.data.insurance_fee_percentage = new_fee_percentage;
+            self.data.insurance_incentive_percentage = new_incentive_percentage;
+
+            Self::emit_event(
+                Self::env(),
+                Event::InsurancePercentagesAdjusted(InsurancePercentagesAdjusted {
+                    new_fee_percentage,
+                    new_incentive_percentage,
+                }),
+            );
+
+            Ok(())
+        }
+
+        /// Cover a loss of `azero` from the insurance fund
+        ///
+        /// Caller must have the owner role (`RoleType::Owner`)
+        /// Insurance AZERO is bonded without minting sAZERO, increasing `total_pooled`
+        /// Any remainder is covered by burning insurance sAZERO of equal value
+        #[ink(message)]
+        pub fn cover_loss(&mut self, azero: Balance) -> Result<(), VaultError> {
+            self.check_version()?;
+
+            let caller = Self::env().caller();
+            let now = Self::env().block_timestamp();
+
+            if caller != self.data.get_role(RoleType::Owner) {
+                return Err(VaultError::InvalidPermissions);
+            }
+            if azero == 0 {
+                return Err(VaultError::ZeroLoss);
+            }
+
+            self.data.update_fees(now);
+
+            let insurance_azero_ = self.data.insurance_azero; // shadow
+            let azero_drawn = if azero < insurance_azero_ { azero } else { insurance_azero_ };
+            let shares_drawn = if azero > azero_drawn {
+                self.get_shares_from_azero(azero - azero_drawn)
+            } else {
+                0
+            };
+            if shares_drawn > self.data.insurance_shares {
+                return Err(VaultError::InsufficientInsurance);
+            }
+
+            if azero_drawn > 0 {
+                self.data.insurance_azero -= azero_drawn;
+                self.data.delegate_bonding(azero_drawn)?;
+            }
+            if shares_drawn > 0 {
+                self.data.insurance_shares -= shares_drawn;
+                self.burn_shares(shares_drawn)?;
+            }
+
+            Self::emit_event(
+                Self::env(),
+                Event::InsuranceDrawn(InsuranceDrawn {
+                    shares: shares_drawn,
+                    azero: azero_drawn,
+                }),
+            );
+
+            self.record_rate_checkpoint();
+
+            Ok(())
+        }
+
+        /// Propose an upgrade of the contract code which can be applied after `delay`
+        ///
+        /// Caller must have the owner role (`RoleType::Owner`)
+        /// Delay must be at least `MIN_CODE_DELAY` so stakers can audit the new code
+        /// Replaces any pending proposal
+        #[ink(message)]
+        pub fn propose_code(&mut self, code_hash: [u8; 32], delay: u64) -> Result<(), VaultError> {
+            self.check_version()?;
+
+            let caller = Self::env().caller();
+            let now = Self::env().block_timestamp();
+
+            if caller != self.data.get_role(RoleType::Owner) {
+                return Err(VaultError::InvalidPermissions);
+            }
+            if delay < MIN_CODE_DELAY {
+                return Err(VaultError::CodeDelay);
+            }
+
+            let effective_time = now + delay;
+            self.data.code_proposal = Some(CodeProposal {
+                code_hash,
+                effective_time,
+            });
+
+            Self::emit_event(
+                Self::env(),
+                Event::CodeProposed(CodeProposed {
+                    code_hash,
+                    effective_time,
+                }),
+            );
+
+            Ok(())
+        }
+
+        /// Cancel the pending code proposal
+        ///
+        /// Caller must have the owner role (`RoleType::Owner`)
+        #[ink(message)]
+        pub fn cancel_code(&mut self) -> Result<(), VaultError> {
+            self.check_version()?;
+
+            let caller = Self::env().caller();
+
+            if caller != self.data.get_role(RoleType::Owner) {
+                return Err(VaultError::InvalidPermissions);
+            }
+
+            let proposal = self.data.code_proposal.take().ok_or(VaultError::NoCodeProposed)?;
+
+            Self::emit_event(
+                Self::env(),
+                Event::CodeCancelled(CodeCancelled {
+                    code_hash: proposal.code_hash,
+                }),
+            );
+
+            Ok(())
+        }
+
+        /// Upgrade the contract to the proposed code by the ink env set_code_hash function
+        ///
+        /// Caller must have the owner role (`RoleType::Owner`)
+        /// Proposal delay must have elapsed
+        /// Storage layout changes must be migrated via `migrate()` after the upgrade
+        /// Packed `VaultData` fields must keep their encoding, see `MIGRATIONS`
+        /// Vaults deployed before versioned storage cannot be upgraded to version 1 and must be redeployed
+        /// See ink documentation for details https://paritytech.github.io/ink/ink_env/fn.set_code_hash.html
+        #[ink(message)]
+        pub fn apply_code(&mut self) -> Result<(), VaultError> {
+            self.check_version()?;
+
+            let caller = Self::env().caller();
+            let now = Self::env().block_timestamp();
+
+            if caller != self.data.get_role(RoleType::Owner) {
+                return Err(VaultError::InvalidPermissions);
+            }
+
+            let proposal = self.data.code_proposal.clone().ok_or(VaultError::NoCodeProposed)?;
+            if now < proposal.effective_time {
+                return Err(VaultError::CodeChangeNotReady);
+            }
+
+            self.data.code_proposal = None;
+
+            let mut old_code_hash = [0u8; 32];
+            old_code_hash.copy_from_slice(Self::env().own_code_hash()?.as_ref());
+            ink::env::set_code_hash(&proposal.code_hash)?;
+
+            Self::emit_event(
+                Self::env(),
+                Event::CodeUpgraded(CodeUpgraded {
+                    old_code_hash,
+                    new_code_hash: proposal.code_hash,
+                }),
+            );
+
+            Ok(())
+        }
+
+        /// Run the ordered storage migrations from the stored version up to `version`
+        ///
+        /// Caller must have the owner role (`RoleType::Owner`)
+        /// Version must be newer than the stored version and not exceed `VAULT_VERSION` of the current code
+        /// All other messages fail with `MigrationPending` until the stored version reaches `VAULT_VERSION`
+        #[ink(message)]
+        pub fn migrate(&mut self, version: u32) -> Result<(), VaultError> {
+            let caller = Self::env().caller();
+
+            if caller != self.data.get_role(RoleType::Owner) {
+                return Err(VaultError::InvalidPermissions);
+            }
+
+            let from_version = self.data.version;
+            if version <= from_version || version > VAULT_VERSION {
+                return Err(VaultError::InvalidVersion);
+            }
+
+            for v in from_version..version {
+                MIGRATIONS[(v - 1) as usize](&mut self.data);
+                self.data.version = v + 1;
+            }
+
+            Self::emit_event(
+                Self::env(),
+                Event::Migrated(Migrated {
+                    from_version,
+                    to_version: version,
+                }),
+            );
+
+            Ok(())
+        }
+
+        /// ======================== Restricted Functions: Adjust Fee Role ========================
+
+        /// Queue a change of the protocol fee
+        ///
+        /// Caller must have the adjust fee role (`RoleType::AdjustFee`)
+        /// See `queue_fee_change()`
+        #[ink(message)]
+        pub fn adjust_fee(&mut self, new_fee: u16) -> Result<(), VaultError> {
+            self.check_version()?;
+
+            self.queue_fee_change(FeeKind::Management, new_fee)
+        }
+
+        /// Queue a change of the compound incentive
+        ///
+        /// Caller must have the adjust fee role (`RoleType::AdjustFee`)
+        /// See `queue_fee_change()`
+        #[ink(message)]
+        pub fn adjust_incentive(&mut self, new_incentive: u16) -> Result<(), VaultError> {
+            self.check_version()?;
+
+            self.queue_fee_change(FeeKind::Incentive, new_incentive)
+        }
+
+        /// Cancel a queued fee change before it is applied
+        ///
+        /// Caller must have the adjust fee role (`RoleType::AdjustFee`)
+        #[ink(message)]
+        pub fn cancel_fee_change(&mut self, kind: FeeKind) -> Result<(), VaultError> {
+            self.check_version()?;
+
+            let caller = Self::env().caller();
+
+            if caller != self.data.get_role(RoleType::AdjustFee) {
+                return Err(VaultError::InvalidPermissions);
+            }
+
+            let proposal = self.data.fee_change_proposals.get(kind).ok_or(VaultError::NoFeeChangeQueued)?;
+            self.data.fee_change_proposals.remove(kind);
+
+            Self::emit_event(
+                Self::env(),
+                Event::FeeChangeCancelled(FeeChangeCancelled {
+                    kind,
+                    new_value: proposal.new_value,
+                }),
+            );
+
+            Ok(())
+        }
+
+        /// Apply a queued fee change once its effective time has passed
+        ///
+        /// Can be called by anyone
+        #[ink(message)]
+        pub fn apply_fee_change(&mut self, kind: FeeKind) -> Result<(), VaultError> {
+            self.check_version()?;
+
+            self.apply_queued_fee_change(kind)
+        }
+
+        /// Queue a change of the instant unlock fee
+        ///
+        /// Caller must have the adjust fee role (`RoleType::AdjustFee`)
+        /// See `queue_fee_change()`
+        #[ink(message)]
+        pub fn adjust_instant_unlock_fee(&mut self, new_instant_unlock_fee: u16) -> Result<(), VaultError> {
+            self.check_version()?;
+
+            self.queue_fee_change(FeeKind::InstantUnlock, new_instant_unlock_fee)
+        }
+
+        /// Queue a change of the portion of the management fee on referred sAZERO redirected to referrers
+        ///
+        /// Caller must have the adjust fee role (`RoleType::AdjustFee`)
+        /// See `queue_fee_change()`
+        #[ink(message)]
+        pub fn adjust_referral_fee(&mut self, new_referral_fee: u16) -> Result<(), VaultError> {
+            self.check_version()?;
+
+            self.queue_fee_change(FeeKind::Referral, new_referral_fee)
+        }
+
+        /// Queue a change of the performance fee charged on compounded rewards
+        ///
+        /// Caller must have the adjust fee role (`RoleType::AdjustFee`)
+        /// See `queue_fee_change()`
+        #[ink(message)]
+        pub fn adjust_performance_fee(&mut self, new_performance_fee: u16) -> Result<(), VaultError> {
+            self.check_version()?;
+
+            self.queue_fee_change(FeeKind::Performance, new_performance_fee)
+        }
+
+        /// Update the keeper reward paid for sending batch unlock requests and withdrawing unbonded AZERO
+        ///
+        /// Caller must have the adjust fee role (`RoleType::AdjustFee`)
+        #[ink(message)]
+        pub fn adjust_keeper_reward(&mut self, new_keeper_reward: KeeperReward) -> Result<(), VaultError> {
+            self.check_version()?;
+
+            let caller = Self::env().caller();
+
+            if caller != self.data.get_role(RoleType::AdjustFee) {
+                return Err(VaultError::InvalidPermissions);
+            }
+            if self.data.keeper_reward == new_keeper_reward {
+                return Err(VaultError::NoChange);
+            }
+            if let KeeperReward::Percentage(bips) = new_keeper_reward {
+                if bips >= BIPS {
+                    return Err(VaultError::InvalidPercent);
+                }
+            }
+
+            self.data.keeper_reward = new_keeper_reward;
+
+            Self::emit_event(
+                Self::env(),
+                Event::KeeperRewardAdjusted(KeeperRewardAdjusted {
+                    new_keeper_reward,
+                }),
+            );
+
+            Ok(())
+        }
+        /// ======================== Restricted Functions: Fee Split Role ========================
+
+        /// Replaces the recipients of withdrawn fees
+        ///
+        /// Caller must have the fee split role (`RoleType::FeeSplit`)
+        /// Weights must be non-zero and sum to BIPS
+        /// An empty list withdraws all fees to the owner
+        #[ink(message)]
+        pub fn set_fee_recipients(&mut self, recipients: Vec<FeeRecipient>) -> Result<(), VaultError> {
+            self.check_version()?;
+
+            let caller = Self::env().caller();
+
+            if caller != self.data.get_role(RoleType::FeeSplit) {
+                return Err(VaultError::InvalidPermissions);
+            }
+            if self.data.fee_recipients == recipients {
+                return Err(VaultError::NoChange);
+            }
+
+            if !recipients.is_empty() {
+                let mut total_weight: u32 = 0;
+                for (i, recipient) in recipients.iter().enumerate() {
+                    if recipient.weight == 0 {
+                        return Err(VaultError::InvalidFeeSplit);
+                    }
+                    if recipients[..i].iter().any(|r| r.account == recipient.account) {
+                        return Err(VaultError::Duplication);
+                    }
+                    total_weight += recipient.weight as u32;
+                }
+                if total_weight != BIPS as u32 {
+                    return Err(VaultError::InvalidFeeSplit);
+                }
+            }
+
+            self.data.fee_recipients = recipients.clone();
+
+            Self::emit_event(
+                Self::env(),
+                Event::FeeRecipientsUpdated(FeeRecipientsUpdated {
+                    recipients,
+                }),
+            );
+
+            Ok(())
+        }
+
+        /// ======================== Restricted Functions: Allowlist Role ========================
+
+        /// Add accounts to the staking allowlist
+        ///
+        /// Caller must have the allowlist role (`RoleType::Allowlist`)
+        #[ink(message)]
+        pub fn add_to_allowlist(&mut self, accounts: Vec<AccountId>) -> Result<(), VaultError> {
+            self.check_version()?;
+
+            let caller = Self::env().caller();
+
+            if caller != self.data.get_role(RoleType::Allowlist) {
+                return Err(VaultError::InvalidPermissions);
+            }
+
+            for account in accounts {
+                if self.data.allowlist.contains(account) {
+                    continue;
+                }
+
+                self.data.allowlist.insert(account, &());
+
+                Self::emit_event(
+                    Self::env(),
+                    Event::AllowlistAdded(AllowlistAdded {
+                        account,
+                    }),
+                );
+            }
+
+            Ok(())
+        }
+
+        /// Remove accounts from the staking allowlist
+        /// Does not affect AZERO which is already staked
+        ///
+        /// Caller must have the allowlist role (`RoleType::Allowlist`)
+        #[ink(message)]
+        pub fn remove_from_allowlist(&mut self, accounts: Vec<AccountId>) -> Result<(), VaultError> {
+            self.check_version()?;
+
+            let caller = Self::env().caller();
+
+            if caller != self.data.get_role(RoleType::Allowlist) {
+                return Err(VaultError::InvalidPermissions);
+            }
+
+            for account in accounts {
+                if !self.data.allowlist.contains(account) {
+                    continue;
+                }
+
+                self.data.allowlist.remove(account);
+
+                Self::emit_event(
+                    Self::env(),
+                    Event::AllowlistRemoved(AllowlistRemoved {
+                        account,
+                    }),
+                );
+            }
+
+            Ok(())
+        }
+
+        /// Toggle whether staking is restricted to allowlisted accounts
+        ///
+        /// Caller must have the allowlist role (`RoleType::Allowlist`)
+        #[ink(message)]
+        pub fn set_allowlist_enabled(&mut self, enabled: bool) -> Result<(), VaultError> {
+            self.check_version()?;
+
+            let caller = Self::env().caller();
+
+            if caller != self.data.get_role(RoleType::Allowlist) {
+                return Err(VaultError::InvalidPermissions);
+            }
+            if self.data.allowlist_enabled == enabled {
+                return Err(VaultError::NoChange);
+            }
 
-            self.redeem(user, unlock_id)?;
+            self.data.allowlist_enabled = enabled;
+
+            Self::emit_event(
+                Self::env(),
+                Event::AllowlistToggled(AllowlistToggled {
+                    enabled,
+                }),
+            );
 
             Ok(())
         }
 
-        /// Compound earned interest for all validators
+        /// Update the Merkle root of allowlisted accounts used by `stake_with_proof`
         ///
-        /// Can be called by anyone
-        /// Caller receives an AZERO incentive based on the total AZERO amount compounded
+        /// Caller must have the allowlist role (`RoleType::Allowlist`)
+        /// Leaves are the Blake2x256 hash of the account, pairs are hashed in sorted order
         #[ink(message)]
-        pub fn compound(&mut self) -> Result<Balance, VaultError> {
-            let caller = Self::env().caller();
+        pub fn set_allowlist_merkle_root(&mut self, merkle_root: Option<[u8; 32]>) -> Result<(), VaultError> {
+            self.check_version()?;
 
-            // Delegate compounding to all nominator pools
-            let (compounded, incentive) = self.data.delegate_compound()?;
+            let caller = Self::env().caller();
 
-            // Send AZERO incentive to caller
-            if incentive > 0 {
-                Self::env().transfer(caller, incentive)?;
+            if caller != self.data.get_role(RoleType::Allowlist) {
+                return Err(VaultError::InvalidPermissions);
+            }
+            if self.data.allowlist_merkle_root == merkle_root {
+                return Err(VaultError::NoChange);
             }
 
+            self.data.allowlist_merkle_root = merkle_root;
+
             Self::emit_event(
                 Self::env(),
-                Event::Compounded(Compounded {
-                    caller,
-                    azero: compounded,
-                    incentive,
-                    virtual_shares: self.get_current_virtual_shares(),
+                Event::AllowlistMerkleRootUpdated(AllowlistMerkleRootUpdated {
+                    merkle_root,
                 }),
             );
 
-            Ok(incentive)
+            Ok(())
         }
 
-        /// =========================== Restricted Functions: Owner Role ===========================
+        /// ========================== Restricted Functions: Pauser Role ==========================
 
-        /// Claim fees by inflating sAZERO supply
+        /// Pause or unpause an operation of the Vault
         ///
-        /// Caller must have the owner role (`role_owner`)
-        /// Mints virtual shares as sAZERO to the owner
-        /// Effectively serves as a compounding for protocol fee
-        /// sets total_shares_virtual to 0
+        /// Caller must have the pauser role (`RoleType::Pauser`)
         #[ink(message)]
-        pub fn withdraw_fees(&mut self) -> Result<(), VaultError> {
+        pub fn set_paused(&mut self, switch: PauseSwitch, paused: bool) -> Result<(), VaultError> {
+            self.check_version()?;
+
             let caller = Self::env().caller();
-            let now = Self::env().block_timestamp();
 
-            if caller != self.data.role_owner {
+            if caller != self.data.get_role(RoleType::Pauser) {
                 return Err(VaultError::InvalidPermissions);
             }
+            if self.data.paused.get(switch) == paused {
+                return Err(VaultError::NoChange);
+            }
 
-            self.data.update_fees(now);
-
-            let shares = self.data.total_shares_virtual;
-            self.mint_shares(shares, self.data.role_owner)?;
-            self.data.total_shares_virtual = 0;
+            self.data.paused.set(switch, paused);
 
             Self::emit_event(
                 Self::env(),
-                Event::FeesWithdrawn(FeesWithdrawn {
-                    shares,
+                Event::PauseToggled(PauseToggled {
+                    switch,
+                    paused,
                 }),
             );
 
             Ok(())
         }
 
-        /// Update the minimum stake amount
+        /// ======================== Restricted Functions: Role Management ========================
+
+        /// Propose a new account for a role
         ///
-        /// Caller must have the owner role (`role_owner`)
+        /// Caller must be the admin for the role
+        /// The role is transferred once the proposed account accepts it via `accept_role()`
         #[ink(message)]
-        pub fn adjust_minimum_stake(&mut self, new_minimum_stake: Balance) -> Result<(), VaultError> {
+        pub fn propose_role_transfer(&mut self, role_type: RoleType, new_account: AccountId) -> Result<(), VaultError> {
+            self.check_version()?;
+
             let caller = Self::env().caller();
 
-            if caller != self.data.role_owner {
+            if caller != self.data.get_role_admin(role_type) {
                 return Err(VaultError::InvalidPermissions);
             }
-            if self.data.minimum_stake == new_minimum_stake {
+
+            let mut pending = self.data.pending_roles.get(role_type).unwrap_or_default();
+            if self.data.get_role(role_type) == new_account || pending.account == Some(new_account) {
                 return Err(VaultError::NoChange);
             }
 
-            self.data.minimum_stake = new_minimum_stake;
+            pending.account = Some(new_account);
+            self.data.set_pending_role(role_type, &pending);
 
             Self::emit_event(
                 Self::env(),
-                Event::MinimumStakeAdjusted(MinimumStakeAdjusted {
-                    new_minimum_stake,
+                Event::RoleTransferProposed(RoleTransferProposed {
+                    role_type,
+                    new_account,
                 }),
             );
 
             Ok(())
         }
 
-        /// Upgrade the contract by the ink env set_code_hash function
+        /// Propose a new admin for a role
         ///
-        /// Caller must have the owner role (`role_owner`)
-        /// See ink documentation for details https://paritytech.github.io/ink/ink_env/fn.set_code_hash.html
+        /// Caller must be the admin for the role
+        /// Administration is transferred once the proposed admin accepts it via `accept_role_admin()`
         #[ink(message)]
-        pub fn set_code(&mut self, code_hash: [u8; 32]) -> Result<(), VaultError> {
+        pub fn propose_role_admin_transfer(
+            &mut self,
+            role_type: RoleType,
+            new_account: AccountId,
+        ) -> Result<(), VaultError> {
+            self.check_version()?;
+
             let caller = Self::env().caller();
 
-            if caller != self.data.role_owner {
+            if caller != self.data.get_role_admin(role_type) {
                 return Err(VaultError::InvalidPermissions);
             }
 
-            ink::env::set_code_hash(&code_hash)?;
+            let mut pending = self.data.pending_roles.get(role_type).unwrap_or_default();
+            if self.data.get_role_admin(role_type) == new_account || pending.admin == Some(new_account) {
+                return Err(VaultError::NoChange);
+            }
+
+            pending.admin = Some(new_account);
+            self.data.set_pending_role(role_type, &pending);
 
             Self::emit_event(
                 Self::env(),
-                Event::NewHash(NewHash {
-                    code_hash,
+                Event::RoleAdminTransferProposed(RoleAdminTransferProposed {
+                    role_type,
+                    new_account,
                 }),
             );
 
             Ok(())
         }
 
-        /// Transfers ownership to a new account
+        /// Cancel all pending transfers of a role
         ///
-        /// Caller must have the owner role (`role_owner`)
+        /// Caller must be the admin for the role
         #[ink(message)]
-        pub fn transfer_role_owner(&mut self, new_account: AccountId) -> Result<(), VaultError> {
+        pub fn cancel_role_transfer(&mut self, role_type: RoleType) -> Result<(), VaultError> {
+            self.check_version()?;
+
             let caller = Self::env().caller();
 
-            if caller != self.data.role_owner {
+            if caller != self.data.get_role_admin(role_type) {
                 return Err(VaultError::InvalidPermissions);
             }
-            if self.data.role_owner == new_account {
+            if !self.data.pending_roles.contains(role_type) {
                 return Err(VaultError::NoChange);
             }
 
-            self.data.role_owner = new_account;
+            self.data.pending_roles.remove(role_type);
 
             Self::emit_event(
                 Self::env(),
-                Event::OwnershipTransferred(OwnershipTransferred {
-                    new_account,
+                Event::RoleTransferCancelled(RoleTransferCancelled {
+                    role_type,
                 }),
             );
 
             Ok(())
         }
 
-        /// ======================== Restricted Functions: Adjust Fee Role ========================
-
-        /// Update the protocol fee
+        /// Accept a proposed role
         ///
-        /// Caller must have the adjust fee role (`role_adjust_fee`)
-        /// Updates the total_shares_virtual accumulator at the old fee level first
+        /// Caller must be the account proposed via `propose_role_transfer()`
         #[ink(message)]
-        pub fn adjust_fee(&mut self, new_fee: u16) -> Result<(), VaultError> {
+        pub fn accept_role(&mut self, role_type: RoleType) -> Result<(), VaultError> {
+            self.check_version()?;
+
             let caller = Self::env().caller();
-            let now = Self::env().block_timestamp();
 
-            if caller != self.data.role_adjust_fee {
+            let mut pending = self.data.pending_roles.get(role_type).unwrap_or_default();
+            if pending.account != Some(caller) {
                 return Err(VaultError::InvalidPermissions);
             }
-            if self.data.fee_percentage == new_fee {
-                return Err(VaultError::NoChange);
-            }
-            if new_fee >= BIPS {
-                return Err(VaultError::InvalidPercent);
-            }
 
-            self.data.update_fees(now);
-            self.data.fee_percentage = new_fee;
+            let mut role = self.data.roles.get(role_type).unwrap();
+            role.account = caller;
+            self.data.roles.insert(role_type, &role);
+
+            pending.account = None;
+            self.data.set_pending_role(role_type, &pending);
 
             Self::emit_event(
                 Self::env(),
-                Event::FeesAdjusted(FeesAdjusted {
-                    new_fee,
-                    virtual_shares: self.data.total_shares_virtual, // updated in update_fees()
+                Event::RoleAccountChanged(RoleAccountChanged {
+                    role_type,
+                    new_account: caller,
                 }),
             );
 
             Ok(())
         }
 
-        /// Update the compound incentive
+        /// Accept administration of a role
         ///
-        /// Caller must have the adjust fee role (`role_adjust_fee`)
+        /// Caller must be the admin proposed via `propose_role_admin_transfer()`
         #[ink(message)]
-        pub fn adjust_incentive(&mut self, new_incentive: u16) -> Result<(), VaultError> {
+        pub fn accept_role_admin(&mut self, role_type: RoleType) -> Result<(), VaultError> {
+            self.check_version()?;
+
             let caller = Self::env().caller();
 
-            if caller != self.data.role_adjust_fee {
+            let mut pending = self.data.pending_roles.get(role_type).unwrap_or_default();
+            if pending.admin != Some(caller) {
                 return Err(VaultError::InvalidPermissions);
             }
-            if self.data.incentive_percentage == new_incentive {
-                return Err(VaultError::NoChange);
-            }
-            if new_incentive >= BIPS {
-                return Err(VaultError::InvalidPercent);
-            }
 
-            self.data.incentive_percentage = new_incentive;
+            let mut role = self.data.roles.get(role_type).unwrap();
+            role.admin = caller;
+            self.data.roles.insert(role_type, &role);
+
+            pending.admin = None;
+            self.data.set_pending_role(role_type, &pending);
 
             Self::emit_event(
                 Self::env(),
-                Event::IncentiveAdjusted(IncentiveAdjusted {
-                    new_incentive,
+                Event::RoleAdminChanged(RoleAdminChanged {
+                    role_type,
+                    new_account: caller,
                 }),
             );
 
             Ok(())
         }
 
-        /// Transfers adjust fee role to a new account
+        /// Permanently give up a role until the admin transfers it to a new account
         ///
-        /// Caller must be the admin for the adjust fee role (`role_adjust_fee_admin`)
+        /// Caller must hold the role
         #[ink(message)]
-        pub fn transfer_role_adjust_fee(&mut self, new_account: AccountId) -> Result<(), VaultError> {
+        pub fn renounce_role(&mut self, role_type: RoleType) -> Result<(), VaultError> {
+            self.check_version()?;
+
             let caller = Self::env().caller();
 
-            if caller != self.data.role_adjust_fee_admin {
+            let mut role = self.data.roles.get(role_type).unwrap();
+            if caller != role.account {
                 return Err(VaultError::InvalidPermissions);
             }
-            if self.data.role_adjust_fee == new_account {
-                return Err(VaultError::NoChange);
-            }
 
-            self.data.role_adjust_fee = new_account;
+            role.account = AccountId::from([0u8; 32]);
+            self.data.roles.insert(role_type, &role);
 
             Self::emit_event(
                 Self::env(),
-                Event::RoleSetFeesTransferred(RoleSetFeesTransferred {
-                    new_account,
+                Event::RoleRenounced(RoleRenounced {
+                    role_type,
+                    account: caller,
                 }),
             );
 
             Ok(())
         }
 
-        /// Transfers administration of adjust fee role to a new account
+        /// Permanently give up administration of a role, freezing the role account
         ///
-        /// Caller must be the admin for the adjust fee role (`role_adjust_fee_admin`)
+        /// Caller must be the admin for the role
+        /// Cancels all pending transfers of the role
         #[ink(message)]
-        pub fn transfer_role_adjust_fee_admin(&mut self, new_account: AccountId) -> Result<(), VaultError> {
+        pub fn renounce_role_admin(&mut self, role_type: RoleType) -> Result<(), VaultError> {
+            self.check_version()?;
+
             let caller = Self::env().caller();
 
-            if caller != self.data.role_adjust_fee_admin {
+            let mut role = self.data.roles.get(role_type).unwrap();
+            if caller != role.admin {
                 return Err(VaultError::InvalidPermissions);
             }
-            if self.data.role_adjust_fee_admin == new_account {
-                return Err(VaultError::NoChange);
-            }
 
-            self.data.role_adjust_fee_admin = new_account;
+            role.admin = AccountId::from([0u8; 32]);
+            self.data.roles.insert(role_type, &role);
+            self.data.pending_roles.remove(role_type);
 
             Self::emit_event(
                 Self::env(),
-                Event::RoleSetFeesAdminTransferred(RoleSetFeesAdminTransferred {
-                    new_account,
+                Event::RoleAdminRenounced(RoleAdminRenounced {
+                    role_type,
+                    account: caller,
                 }),
             );
 
@@ -794,30 +3138,206 @@ mod vault {
             self.data.get_batch_unlock_id(Self::env().block_timestamp())
         }
 
+        /// Returns the oldest batch id which may not have been sent
+        #[ink(message)]
+        pub fn get_oldest_unsent_batch_id(&self) -> u64 {
+            self.data.oldest_unsent_batch_id
+        }
+
+        #[ink(message)]
+        pub fn get_referral_fee_percentage(&self) -> u16 {
+            self.data.referral_fee_percentage
+        }
+
+        #[ink(message)]
+        pub fn get_referral_whitelist_enabled(&self) -> bool {
+            self.data.referral_whitelist_enabled
+        }
+
+        #[ink(message)]
+        pub fn is_referrer_registered(&self, referral_id: AccountId) -> bool {
+            self.data.referral_whitelist.contains(referral_id)
+        }
+
+        /// Returns the accumulated volume and sAZERO attributed to a referral id
+        #[ink(message)]
+        pub fn get_referrer_info(&self, referral_id: AccountId) -> ReferrerInfo {
+            self.data.referrers.get(referral_id).unwrap_or_default()
+        }
+
+        /// Returns the referral rewards (sAZERO) which can be claimed at the current block timestamp
+        #[ink(message)]
+        pub fn get_referral_rewards(&self, referral_id: AccountId) -> Balance {
+            let now = Self::env().block_timestamp();
+            let reward_per_share = self.data.get_referral_reward_per_share_at_time(now);
+            self.data.get_referral_rewards(referral_id, reward_per_share)
+        }
+
+        #[ink(message)]
+        pub fn get_performance_fee_percentage(&self) -> u16 {
+            self.data.performance_fee_percentage
+        }
+
+        /// Returns the configured fees alongside the virtual shares accrued by each
+        #[ink(message)]
+        pub fn get_fee_breakdown(&self) -> FeeBreakdown {
+            FeeBreakdown {
+                fee_percentage: self.data.fee_percentage,
+                performance_fee_percentage: self.data.performance_fee_percentage,
+                virtual_shares: self.get_current_virtual_shares(),
+                performance_fee_shares: self.data.total_performance_fee_shares,
+                performance_fee_azero: self.data.total_performance_fee_azero,
+            }
+        }
+
+        #[ink(message)]
+        pub fn get_keeper_reward(&self) -> KeeperReward {
+            self.data.keeper_reward
+        }
+
+        #[ink(message)]
+        pub fn get_max_batches_per_dispatch(&self) -> u32 {
+            self.data.max_batches_per_dispatch
+        }
+
         #[ink(message)]
         pub fn get_creation_time(&self) -> u64 {
             self.data.creation_time
         }
 
+        /// Returns the storage layout version of the Vault
+        #[ink(message)]
+        pub fn get_version(&self) -> u32 {
+            self.data.version
+        }
+
+        /// Returns the code hash awaiting its timelock, if any
+        #[ink(message)]
+        pub fn get_code_proposal(&self) -> Option<CodeProposal> {
+            self.data.code_proposal.clone()
+        }
+
+        #[ink(message)]
+        pub fn get_role(&self, role_type: RoleType) -> AccountId {
+            self.data.get_role(role_type)
+        }
+
+        #[ink(message)]
+        pub fn get_role_admin(&self, role_type: RoleType) -> AccountId {
+            self.data.get_role_admin(role_type)
+        }
+
+        /// Returns the account and admin of every role
+        #[ink(message)]
+        pub fn get_roles(&self) -> Vec<(RoleType, Role)> {
+            ROLE_TYPES
+                .into_iter()
+                .map(|role_type| (role_type, self.data.roles.get(role_type).unwrap()))
+                .collect()
+        }
+
+        /// Returns the proposed accounts and admins of roles awaiting acceptance
+        #[ink(message)]
+        pub fn get_pending_role_transfers(&self) -> Vec<(RoleType, PendingRole)> {
+            ROLE_TYPES
+                .into_iter()
+                .filter_map(|role_type| self.data.pending_roles.get(role_type).map(|pending| (role_type, pending)))
+                .collect()
+        }
+
+        /// Returns which operations are currently paused
+        #[ink(message)]
+        pub fn get_pause_state(&self) -> PauseState {
+            self.data.paused.clone()
+        }
+
+        #[ink(message)]
+        pub fn get_allowlist_enabled(&self) -> bool {
+            self.data.allowlist_enabled
+        }
+
+        #[ink(message)]
+        pub fn get_allowlist_merkle_root(&self) -> Option<[u8; 32]> {
+            self.data.allowlist_merkle_root
+        }
+
+        /// Returns whether an account was added to the staking allowlist
+        #[ink(message)]
+        pub fn is_allowlisted(&self, account: AccountId) -> bool {
+            self.data.allowlist.contains(account)
+        }
+
+        /// Returns whether an account was added to the staking allowlist or is proven to be in the Merkle root
+        #[ink(message)]
+        pub fn is_allowlisted_with_proof(&self, account: AccountId, proof: Vec<[u8; 32]>) -> bool {
+            if self.data.allowlist.contains(account) {
+                return true;
+            }
+            match self.data.allowlist_merkle_root {
+                Some(root) => verify_merkle_proof(root, account, &proof),
+                None => false,
+            }
+        }
+
+        /// Returns the recipients of withdrawn fees, empty when all fees are withdrawn to the owner
+        #[ink(message)]
+        pub fn get_fee_recipients(&self) -> Vec<FeeRecipient> {
+            self.data.fee_recipients.clone()
+        }
+
+        #[ink(message)]
+        pub fn get_donation_duration(&self) -> u64 {
+            self.data.donation_duration
+        }
+
+        /// Returns the donated AZERO which is not yet released into the redemption ratio
+        #[ink(message)]
+        pub fn get_unreleased_donations(&self) -> Balance {
+            let stream = &self.data.donation_stream;
+            stream.remaining - self.data.get_stream_release(stream, Self::env().block_timestamp())
+        }
+
+        #[ink(message)]
+        pub fn get_reward_vesting_period(&self) -> u64 {
+            self.data.reward_vesting_period
+        }
+
+        /// Returns the compounded AZERO which is not yet vested into the redemption ratio
         #[ink(message)]
-        pub fn get_role_owner(&self) -> AccountId {
-            self.data.role_owner
+        pub fn get_unvested_rewards(&self) -> Balance {
+            let stream = &self.data.reward_stream;
+            stream.remaining - self.data.get_stream_release(stream, Self::env().block_timestamp())
         }
 
+        /// Returns the portions of withdrawn fees and compounding incentives retained by the insurance fund
         #[ink(message)]
-        pub fn get_role_adjust_fee(&self) -> AccountId {
-            self.data.role_adjust_fee
+        pub fn get_insurance_percentages(&self) -> (u16, u16) {
+            (self.data.insurance_fee_percentage, self.data.insurance_incentive_percentage)
         }
 
+        /// Returns the sAZERO and AZERO held by the insurance fund
         #[ink(message)]
-        pub fn get_role_adjust_fee_admin(&self) -> AccountId {
-            self.data.role_adjust_fee_admin
+        pub fn get_insurance_balance(&self) -> (Balance, Balance) {
+            (self.data.insurance_shares, self.data.insurance_azero)
+        }
+
+        /// Returns the total AZERO value of the insurance fund
+        #[ink(message)]
+        pub fn get_insurance_value(&self) -> Balance {
+            self.data.insurance_azero + self.get_azero_from_shares(self.data.insurance_shares)
         }
 
         /// Returns the total amount of bonded AZERO
+        /// Excludes unvested rewards and unreleased donations
         #[ink(message)]
         pub fn get_total_pooled(&self) -> Balance {
-            self.data.total_pooled
+            self.data.total_pooled + self.data.get_pending_release(Self::env().block_timestamp())
+        }
+
+        /// Returns the total amount of AZERO backing sAZERO including the liquidity buffer
+        #[ink(message)]
+        pub fn get_total_value(&self) -> Balance {
+            self.data.get_total_value_at(Self::env().block_timestamp())
         }
 
         /// Returns the shares effectively in circulation by the protocol including:
@@ -840,6 +3360,34 @@ mod vault {
             self.data.minimum_stake
         }
 
+        #[ink(message)]
+        pub fn get_max_total_pooled(&self) -> Balance {
+            self.data.max_total_pooled
+        }
+
+        #[ink(message)]
+        pub fn get_max_account_stake(&self) -> Option<Balance> {
+            self.data.max_account_stake
+        }
+
+        /// Returns the net AZERO staked by an account which counts towards `max_account_stake`
+        #[ink(message)]
+        pub fn get_account_net_staked(&self, account: AccountId) -> Balance {
+            self.data.account_net_staked.get(account).unwrap_or(0)
+        }
+
+        /// Returns the AZERO which can be staked before reaching `max_total_pooled`
+        #[ink(message)]
+        pub fn get_remaining_capacity(&self) -> Balance {
+            self.data.get_remaining_capacity()
+        }
+
+        /// Returns the AZERO which can be staked by an account given both staking caps
+        #[ink(message)]
+        pub fn get_account_remaining_capacity(&self, account: AccountId) -> Balance {
+            self.data.get_account_remaining_capacity(account)
+        }
+
         #[ink(message)]
         pub fn get_fee_percentage(&self) -> u16 {
             self.data.fee_percentage
@@ -849,6 +3397,51 @@ mod vault {
         pub fn get_incentive_percentage(&self) -> u16 {
             self.data.incentive_percentage
         }
+
+        /// Returns the queued change of a fee if any
+        #[ink(message)]
+        pub fn get_pending_fee_change(&self, kind: FeeKind) -> Option<FeeChangeProposal> {
+            self.data.fee_change_proposals.get(kind)
+        }
+
+        /// Returns the fee change delay, minimum period between changes, and maximum change of a single change
+        #[ink(message)]
+        pub fn get_fee_change_limits(&self) -> (u64, u64, u16) {
+            (self.data.fee_change_delay, self.data.fee_change_period, self.data.max_fee_change)
+        }
+
+        /// Returns the queued change of the fee change limits if any
+        #[ink(message)]
+        pub fn get_pending_fee_change_limits(&self) -> Option<FeeChangeLimitsProposal> {
+            self.data.fee_change_limits_proposal.clone()
+        }
+
+        /// Returns the AZERO available for instant unlocks
+        #[ink(message)]
+        pub fn get_liquidity_buffer(&self) -> Balance {
+            self.data.liquidity_buffer
+        }
+
+        /// Returns the AZERO being unbonded to replenish the liquidity buffer
+        #[ink(message)]
+        pub fn get_buffer_unbonding(&self) -> Balance {
+            self.data.buffer_unbonding
+        }
+
+        #[ink(message)]
+        pub fn get_buffer_target_percentage(&self) -> u16 {
+            self.data.buffer_target_percentage
+        }
+
+        #[ink(message)]
+        pub fn get_instant_unlock_fee_percentage(&self) -> u16 {
+            self.data.instant_unlock_fee_percentage
+        }
+
+        #[ink(message)]
+        pub fn get_max_instant_unlock(&self) -> Balance {
+            self.data.max_instant_unlock
+        }
         
         #[ink(message)]
         pub fn get_share_token_contract(&self) -> AccountId {
@@ -863,16 +3456,57 @@ mod vault {
         /// Calculate the value of AZERO in terms of sAZERO
         #[ink(message)]
         pub fn get_shares_from_azero(&self, azero: Balance) -> Balance {
-            let total_pooled_ = self.data.total_pooled; // shadow
-            if total_pooled_ == 0 {
+            let total_value = self.data.get_total_value_at(Self::env().block_timestamp());
+            if total_value == 0 {
                 // This happens upon initial stake
                 // Also known as 1:1 redemption ratio
                 azero
             } else {
-                self.data.pro_rata(azero, self.get_total_shares(), total_pooled_)
+                self.data.pro_rata(azero, self.get_total_shares(), total_value)
             }
         }
 
+        /// Calculate the value of sAZERO in terms of AZERO with RATE_DECIMALS (12) precision
+        /// Read-only equivalent of `RateProvider::get_rate`
+        #[ink(message)]
+        pub fn get_rate(&self) -> u128 {
+            // Because both RATE_DECIMALS and sAZERO.decimals() are 12,
+            // no further adjustment is necessary
+            self.get_azero_from_shares(1e12 as u128)
+        }
+
+        /// Returns the rate of the latest checkpoint at or before `timestamp`
+        /// Returns None when `timestamp` precedes the retained checkpoints
+        #[ink(message)]
+        pub fn get_rate_at(&self, timestamp: Timestamp) -> Option<u128> {
+            self.data.get_rate_at(timestamp)
+        }
+
+        /// Returns the time-weighted average rate over the trailing `window` of time
+        /// Returns None when the retained checkpoints do not cover the window
+        #[ink(message)]
+        pub fn get_twap(&self, window: u64) -> Option<u128> {
+            self.data.get_twap(Self::env().block_timestamp(), window)
+        }
+
+        /// Returns the annualized yields of compound results within the trailing `window` of time
+        /// Includes gross yield, net yield after fees, and realized yield of each agent
+        #[ink(message)]
+        pub fn get_estimated_apy(&self, window: u64) -> ApyEstimate {
+            self.data.get_estimated_apy(Self::env().block_timestamp(), window)
+        }
+
+        #[ink(message)]
+        pub fn get_checkpoint_granularity(&self) -> u64 {
+            self.data.checkpoint_granularity
+        }
+
+        /// Returns the AZERO lost by an agent's nomination pool as reported by the owner
+        #[ink(message)]
+        pub fn get_agent_loss(&self, agent: AccountId) -> Balance {
+            self.data.agent_losses.get(agent).unwrap_or(0)
+        }
+
         /// Calculate the value of sAZERO in terms of AZERO
         #[ink(message)]
         pub fn get_azero_from_shares(&self, shares: Balance) -> Balance {
@@ -881,14 +3515,31 @@ mod vault {
                 // This should never happen
                 0
             } else {
-                self.data.pro_rata(shares, self.data.total_pooled, total_shares)
+                self.data.pro_rata(shares, self.data.get_total_value_at(Self::env().block_timestamp()), total_shares)
             }
         }
 
         /// Returns the unlock requests for a given user
         #[ink(message)]
         pub fn get_unlock_requests(&self, user: AccountId) -> Vec<UnlockRequest> {
-            self.data.user_unlock_requests.get(user).unwrap_or(Vec::new())
+            self.data.user_unlock_requests
+                .get(user)
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|unlock_id| self.data.unlock_requests.get(unlock_id))
+                .collect()
+        }
+
+        /// Returns the unlock ids for a given user in the order they were received
+        #[ink(message)]
+        pub fn get_unlock_request_ids(&self, user: AccountId) -> Vec<u128> {
+            self.data.user_unlock_requests.get(user).unwrap_or_default()
+        }
+
+        /// Returns the unlock request for the given unlock id
+        #[ink(message)]
+        pub fn get_unlock_request(&self, unlock_id: u128) -> Option<UnlockRequest> {
+            self.data.unlock_requests.get(unlock_id)
         }
 
         /// Returns the number of unlock requests made by a given user
@@ -897,6 +3548,58 @@ mod vault {
             self.data.user_unlock_requests.get(user).unwrap_or(Vec::new()).len() as u128
         }
 
+        /// Returns the status and AZERO value of a given user's unlock requests
+        ///
+        /// Pending unlock requests are valued at the current redemption ratio
+        /// Paginated by `offset` and `limit` over the user's unlock requests in the order they were received
+        #[ink(message)]
+        pub fn get_unlock_request_details(
+            &self,
+            user: AccountId,
+            offset: u32,
+            limit: u32,
+        ) -> Vec<UnlockRequestDetails> {
+            let now = Self::env().block_timestamp();
+
+            self.data.user_unlock_requests
+                .get(user)
+                .unwrap_or_default()
+                .into_iter()
+                .skip(offset as usize)
+                .take(limit as usize)
+                .filter_map(|unlock_id| {
+                    self.data.unlock_requests
+                        .get(unlock_id)
+                        .map(|unlock_request| self.get_unlock_request_details_of(unlock_id, &unlock_request, now))
+                })
+                .collect()
+        }
+
+        /// Returns the aggregated state of a given user's unlock requests
+        #[ink(message)]
+        pub fn get_user_summary(&self, user: AccountId) -> UserUnlockSummary {
+            let now = Self::env().block_timestamp();
+
+            let mut summary = UserUnlockSummary {
+                pending_shares: 0,
+                cooldown_azero: 0,
+                claimable_azero: 0,
+            };
+
+            for unlock_id in self.data.user_unlock_requests.get(user).unwrap_or_default().into_iter() {
+                if let Some(unlock_request) = self.data.unlock_requests.get(unlock_id) {
+                    let details = self.get_unlock_request_details_of(unlock_id, &unlock_request, now);
+                    match details.status {
+                        UnlockRequestStatus::Pending => summary.pending_shares += details.share_amount,
+                        UnlockRequestStatus::Cooldown => summary.cooldown_azero += details.azero,
+                        UnlockRequestStatus::Claimable => summary.claimable_azero += details.azero,
+                    }
+                }
+            }
+
+            summary
+        }
+
         /// Returns the information of a batch unlock request for the given batch id
         #[ink(message)]
         pub fn get_batch_unlock_requests(&self, batch_id: u64) -> (u128, Option<u128>, Option<Timestamp>) {